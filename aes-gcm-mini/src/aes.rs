@@ -1,114 +1,326 @@
-use crate::utils::{bits_to_byte, byte_to_bits, word_xor, Block, Stream};
+use crate::ct;
+use crate::profile::{measure, Phase};
+use crate::t_table;
+use crate::utils::{
+    bits_to_byte, block_to_state, byte_to_bits, state_to_block, state_to_stream, stream_to_state,
+    wipe, Block, State, Stream,
+};
+
+/// An AES key of one of the sizes defined in FIPS-197.
+pub trait AesKey: Copy {
+    /// Encrypts a single block under this key.
+    fn encrypt_block(&self, block: Block) -> Block;
+    /// Decrypts a single block under this key.
+    fn decrypt_block(&self, block: Block) -> Block;
+
+    /// Encrypts a single block given as bytes, which the modes work with.
+    fn encrypt_stream(&self, stream: Stream) -> Stream {
+        let block = self.encrypt_block(state_to_block(stream_to_state(stream)));
+        state_to_stream(block_to_state(block))
+    }
+
+    /// Decrypts a single block given as bytes.
+    fn decrypt_stream(&self, stream: Stream) -> Stream {
+        let block = self.decrypt_block(state_to_block(stream_to_state(stream)));
+        state_to_stream(block_to_state(block))
+    }
+}
+
+impl AesKey for [u8; 16] {
+    fn encrypt_block(&self, block: Block) -> Block {
+        aes(block, *self)
+    }
+
+    fn decrypt_block(&self, block: Block) -> Block {
+        aes_inv(block, *self)
+    }
+
+    fn encrypt_stream(&self, stream: Stream) -> Stream {
+        state_to_stream(Aes128::new(*self).encrypt_state(stream_to_state(stream)))
+    }
+
+    fn decrypt_stream(&self, stream: Stream) -> Stream {
+        state_to_stream(Aes128::new(*self).decrypt_state(stream_to_state(stream)))
+    }
+}
+
+impl AesKey for [u8; 24] {
+    fn encrypt_block(&self, block: Block) -> Block {
+        aes192(block, *self)
+    }
+
+    fn decrypt_block(&self, block: Block) -> Block {
+        aes192_inv(block, *self)
+    }
+
+    fn encrypt_stream(&self, stream: Stream) -> Stream {
+        state_to_stream(Aes192::new(*self).encrypt_state(stream_to_state(stream)))
+    }
+
+    fn decrypt_stream(&self, stream: Stream) -> Stream {
+        state_to_stream(Aes192::new(*self).decrypt_state(stream_to_state(stream)))
+    }
+}
+
+impl AesKey for [u8; 32] {
+    fn encrypt_block(&self, block: Block) -> Block {
+        aes256(block, *self)
+    }
+
+    fn decrypt_block(&self, block: Block) -> Block {
+        aes256_inv(block, *self)
+    }
+
+    fn encrypt_stream(&self, stream: Stream) -> Stream {
+        state_to_stream(Aes256::new(*self).encrypt_state(stream_to_state(stream)))
+    }
+
+    fn decrypt_stream(&self, stream: Stream) -> Stream {
+        state_to_stream(Aes256::new(*self).decrypt_state(stream_to_state(stream)))
+    }
+}
+
+/// An AES key expanded into its round keys once, instead of for every block as the byte array
+/// keys are.
+///
+/// The modes take a reference to it as their key. With the `zeroize` feature, the round keys
+/// are wiped when the cipher is dropped, as are the schedules the byte array keys expand into.
+#[derive(Clone)]
+pub struct AesCipher<const W: usize> {
+    key_expanded: [u32; W],
+}
+
+/// AES-128 with its 44-word key schedule.
+pub type Aes128 = AesCipher<44>;
+/// AES-192 with its 52-word key schedule.
+pub type Aes192 = AesCipher<52>;
+/// AES-256 with its 60-word key schedule.
+pub type Aes256 = AesCipher<60>;
+
+impl Aes128 {
+    pub fn new(key: [u8; 16]) -> Self {
+        Self {
+            key_expanded: key_expansion(key),
+        }
+    }
+}
+
+impl Aes192 {
+    pub fn new(key: [u8; 24]) -> Self {
+        Self {
+            key_expanded: expand_key(&key),
+        }
+    }
+}
+
+impl Aes256 {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self {
+            key_expanded: expand_key(&key),
+        }
+    }
+}
+
+impl<const W: usize> AesCipher<W> {
+    fn encrypt_state(&self, state: State) -> State {
+        cipher(state, &self.key_expanded)
+    }
+
+    fn decrypt_state(&self, state: State) -> State {
+        inv_cipher(state, &self.key_expanded)
+    }
+}
+
+impl<const W: usize> AesKey for &AesCipher<W> {
+    fn encrypt_block(&self, block: Block) -> Block {
+        state_to_block(self.encrypt_state(block_to_state(block)))
+    }
+
+    fn decrypt_block(&self, block: Block) -> Block {
+        state_to_block(self.decrypt_state(block_to_state(block)))
+    }
+
+    fn encrypt_stream(&self, stream: Stream) -> Stream {
+        state_to_stream(self.encrypt_state(stream_to_state(stream)))
+    }
+
+    fn decrypt_stream(&self, stream: Stream) -> Stream {
+        state_to_stream(self.decrypt_state(stream_to_state(stream)))
+    }
+}
+
+impl<const W: usize> Drop for AesCipher<W> {
+    fn drop(&mut self) {
+        wipe(&mut self.key_expanded);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const W: usize> zeroize::ZeroizeOnDrop for AesCipher<W> {}
 
 // AES encryption function
 pub fn aes(block: Block, key: Stream) -> Block {
-    let key_expanded = key_expansion(key);
-    let first_round_key: Block = [
-        key_expanded[0],
-        key_expanded[1],
-        key_expanded[2],
-        key_expanded[3],
-    ];
-    let mut state = add_round_key(block, first_round_key);
+    state_to_block(Aes128::new(key).encrypt_state(block_to_state(block)))
+}
+
+// AES-192 encryption function
+pub fn aes192(block: Block, key: [u8; 24]) -> Block {
+    state_to_block(Aes192::new(key).encrypt_state(block_to_state(block)))
+}
+
+// AES-256 encryption function
+pub fn aes256(block: Block, key: [u8; 32]) -> Block {
+    state_to_block(Aes256::new(key).encrypt_state(block_to_state(block)))
+}
+
+// AES encryption function with the T-table round function, regardless of the `t-table` feature
+pub fn aes_t_table(block: Block, key: Stream) -> Block {
+    let state = measure(Phase::Aes, || {
+        t_table::cipher(block_to_state(block), &Aes128::new(key).key_expanded)
+    });
+    state_to_block(state)
+}
+
+// AES decryption function
+pub fn aes_inv(block: Block, key: Stream) -> Block {
+    state_to_block(Aes128::new(key).decrypt_state(block_to_state(block)))
+}
+
+// AES-192 decryption function
+pub fn aes192_inv(block: Block, key: [u8; 24]) -> Block {
+    state_to_block(Aes192::new(key).decrypt_state(block_to_state(block)))
+}
+
+// AES-256 decryption function
+pub fn aes256_inv(block: Block, key: [u8; 32]) -> Block {
+    state_to_block(Aes256::new(key).decrypt_state(block_to_state(block)))
+}
+
+// Cipher function, the number of rounds follows from the length of the key schedule
+fn cipher(state: State, key_expanded: &[u32]) -> State {
+    measure(Phase::Aes, || {
+        if cfg!(all(feature = "t-table", not(feature = "constant-time"))) {
+            return t_table::cipher(state, key_expanded);
+        }
+        let rounds = key_expanded.len() / 4 - 1;
+        let mut state = add_round_key(state, round_key(key_expanded, 0));
+
+        for round in 1..rounds {
+            state = sub_bytes(state);
+            state = shift_rows(state);
+            state = mix_columns(state);
+            state = add_round_key(state, round_key(key_expanded, round));
+        }
 
-    for round in 1..10 {
         state = sub_bytes(state);
         state = shift_rows(state);
-        state = mix_columns(state);
-        let round_key: Block = [
-            key_expanded[round * 4],
-            key_expanded[round * 4 + 1],
-            key_expanded[round * 4 + 2],
-            key_expanded[round * 4 + 3],
-        ];
-        state = add_round_key(state, round_key);
-    }
+        state = add_round_key(state, round_key(key_expanded, rounds));
+        state
+    })
+}
 
-    state = sub_bytes(state);
-    state = shift_rows(state);
-    let final_round_key: Block = [
-        key_expanded[40],
-        key_expanded[41],
-        key_expanded[42],
-        key_expanded[43],
-    ];
-    state = add_round_key(state, final_round_key);
-    state
+// Inverse cipher function, applies the rounds of the cipher in reverse order
+fn inv_cipher(state: State, key_expanded: &[u32]) -> State {
+    measure(Phase::Aes, || {
+        let rounds = key_expanded.len() / 4 - 1;
+        let mut state = add_round_key(state, round_key(key_expanded, rounds));
+
+        for round in (1..rounds).rev() {
+            state = inv_shift_rows(state);
+            state = inv_sub_bytes(state);
+            state = add_round_key(state, round_key(key_expanded, round));
+            state = inv_mix_columns(state);
+        }
+
+        state = inv_shift_rows(state);
+        state = inv_sub_bytes(state);
+        state = add_round_key(state, round_key(key_expanded, 0));
+        state
+    })
+}
+
+// Round key of the given round, as four words of the key schedule
+fn round_key(key_expanded: &[u32], round: usize) -> State {
+    [
+        key_expanded[round * 4],
+        key_expanded[round * 4 + 1],
+        key_expanded[round * 4 + 2],
+        key_expanded[round * 4 + 3],
+    ]
 }
 
 // Key expansion function
-pub(crate) fn key_expansion(key: [u8; 16]) -> [[u8; 4]; 44] {
-    let mut key_expanded: [[u8; 4]; 44] = [[0; 4]; 44];
+pub(crate) fn key_expansion(key: [u8; 16]) -> [u32; 44] {
+    expand_key::<44>(&key)
+}
 
-    for i in 0..4 {
-        let mut temp = [0; 4];
-        for j in 0..4 {
-            temp[j] = key[i * 4 + j];
+// Key expansion for any key size, `W` is the number of words in the key schedule
+fn expand_key<const W: usize>(key: &[u8]) -> [u32; W] {
+    measure(Phase::KeyExpansion, || {
+        let nk = key.len() / 4;
+        let mut key_expanded: [u32; W] = [0; W];
+
+        for i in 0..nk {
+            let mut temp = [0; 4];
+            for j in 0..4 {
+                temp[j] = key[i * 4 + j];
+            }
+            key_expanded[i] = u32::from_be_bytes(temp);
         }
-        key_expanded[i] = temp;
-    }
 
-    for round in 4..44 {
-        let mut temp = key_expanded[round - 1];
-        if round % 4 == 0 {
-            temp = sub_word(rot_word(temp));
-            temp = word_xor(temp, round_constant((round / 4) as u8 - 1));
+        for round in nk..W {
+            let mut temp = key_expanded[round - 1];
+            if round % nk == 0 {
+                temp = sub_word(rot_word(temp)) ^ round_constant((round / nk) as u8 - 1);
+            } else if nk > 6 && round % nk == 4 {
+                temp = sub_word(temp);
+            }
+            key_expanded[round] = key_expanded[round - nk] ^ temp;
         }
-        key_expanded[round] = word_xor(key_expanded[round - 4], temp);
-    }
 
-    key_expanded
+        key_expanded
+    })
 }
 
 // SubWord and RotWord functions
-fn sub_word(mut word: [u8; 4]) -> [u8; 4] {
-    for i in 0..4 {
-        word[i] = sbox(word[i]);
+fn sub_word(word: u32) -> u32 {
+    if cfg!(feature = "constant-time") {
+        return ct::sub_word(word);
+    }
+    let mut bytes = word.to_be_bytes();
+    for byte in bytes.iter_mut() {
+        *byte = sbox(*byte);
     }
-    word
+    u32::from_be_bytes(bytes)
 }
 
-fn rot_word(word: [u8; 4]) -> [u8; 4] {
-    [word[1], word[2], word[3], word[0]]
+fn rot_word(word: u32) -> u32 {
+    word.rotate_left(8)
 }
 
 // Round constant function
-fn round_constant(round: u8) -> [u8; 4] {
+fn round_constant(round: u8) -> u32 {
     let constants = [
-        [0x01, 0x00, 0x00, 0x00],
-        [0x02, 0x00, 0x00, 0x00],
-        [0x04, 0x00, 0x00, 0x00],
-        [0x08, 0x00, 0x00, 0x00],
-        [0x10, 0x00, 0x00, 0x00],
-        [0x20, 0x00, 0x00, 0x00],
-        [0x40, 0x00, 0x00, 0x00],
-        [0x80, 0x00, 0x00, 0x00],
-        [0x1b, 0x00, 0x00, 0x00],
-        [0x36, 0x00, 0x00, 0x00],
+        0x01000000, 0x02000000, 0x04000000, 0x08000000, 0x10000000, 0x20000000, 0x40000000,
+        0x80000000, 0x1b000000, 0x36000000,
     ];
     constants[round as usize]
 }
 
 // AddRoundKey function
-pub fn add_round_key(state: Block, key: Block) -> Block {
-    let mut new_state: Block = [[0; 4]; 4];
+fn add_round_key(state: State, key: State) -> State {
+    let mut new_state: State = [0; 4];
     for i in 0..4 {
-        for j in 0..4 {
-            new_state[i][j] = state[i][j] ^ key[j][i];
-        }
+        new_state[i] = state[i] ^ key[i];
     }
     new_state
 }
 
 // MixColumns function
-fn mix_columns(state: Block) -> Block {
-    let mut new_state: Block = state;
+fn mix_columns(state: State) -> State {
+    let mut new_state: State = state;
     for i in 0..4 {
-        let column = [state[0][i], state[1][i], state[2][i], state[3][i]];
-        let new_column = mix_column(column);
-        for j in 0..4 {
-            new_state[j][i] = new_column[j];
-        }
+        new_state[i] = u32::from_be_bytes(mix_column(state[i].to_be_bytes()));
     }
     new_state
 }
@@ -122,16 +334,55 @@ fn mix_column(column: [u8; 4]) -> [u8; 4] {
     new_column
 }
 
+// InvMixColumns function
+fn inv_mix_columns(state: State) -> State {
+    let mut new_state: State = state;
+    for i in 0..4 {
+        new_state[i] = u32::from_be_bytes(inv_mix_column(state[i].to_be_bytes()));
+    }
+    new_state
+}
+
+fn inv_mix_column(column: [u8; 4]) -> [u8; 4] {
+    let mut new_column = [0; 4];
+    new_column[0] =
+        gfmul14(column[0]) ^ gfmul11(column[1]) ^ gfmul13(column[2]) ^ gfmul9(column[3]);
+    new_column[1] =
+        gfmul9(column[0]) ^ gfmul14(column[1]) ^ gfmul11(column[2]) ^ gfmul13(column[3]);
+    new_column[2] =
+        gfmul13(column[0]) ^ gfmul9(column[1]) ^ gfmul14(column[2]) ^ gfmul11(column[3]);
+    new_column[3] =
+        gfmul11(column[0]) ^ gfmul13(column[1]) ^ gfmul9(column[2]) ^ gfmul14(column[3]);
+    new_column
+}
+
+fn gfmul9(byte: u8) -> u8 {
+    gfmulx(gfmulx(gfmulx(byte))) ^ byte
+}
+
+fn gfmul11(byte: u8) -> u8 {
+    gfmulx(gfmulx(gfmulx(byte)) ^ byte) ^ byte
+}
+
+fn gfmul13(byte: u8) -> u8 {
+    gfmulx(gfmulx(gfmulx(byte) ^ byte)) ^ byte
+}
+
+fn gfmul14(byte: u8) -> u8 {
+    gfmulx(gfmulx(gfmulx(byte) ^ byte) ^ byte)
+}
+
 fn gfmul3(byte: u8) -> u8 {
     gfmulx(byte) ^ byte
 }
 
 fn gfmulx(byte: u8) -> u8 {
+    if cfg!(feature = "constant-time") {
+        return ct::xtime(byte);
+    }
     let bits = byte_to_bits(byte);
     let mut result = [0; 8];
-    for i in 0..7 {
-        result[i] = bits[i + 1];
-    }
+    result[..7].copy_from_slice(&bits[1..]);
     let mut result_byte = bits_to_byte(result);
     if bits[0] == 1 {
         result_byte ^= 0x1b;
@@ -139,54 +390,116 @@ fn gfmulx(byte: u8) -> u8 {
     result_byte
 }
 
-// ShiftRows function
-fn shift_rows(state: Block) -> Block {
-    let mut new_state = state;
-    new_state[1] = [state[1][1], state[1][2], state[1][3], state[1][0]];
-    new_state[2] = [state[2][2], state[2][3], state[2][0], state[2][1]];
-    new_state[3] = [state[3][3], state[3][0], state[3][1], state[3][2]];
+// ShiftRows function, row `r` is byte `r` of every column word counted from the most
+// significant one, and moves `r` columns to the left
+fn shift_rows(state: State) -> State {
+    let mut new_state: State = [0; 4];
+    for c in 0..4 {
+        new_state[c] = (state[c] & 0xff000000)
+            | (state[(c + 1) % 4] & 0x00ff0000)
+            | (state[(c + 2) % 4] & 0x0000ff00)
+            | (state[(c + 3) % 4] & 0x000000ff);
+    }
+    new_state
+}
+
+// InvShiftRows function
+fn inv_shift_rows(state: State) -> State {
+    let mut new_state: State = [0; 4];
+    for c in 0..4 {
+        new_state[c] = (state[c] & 0xff000000)
+            | (state[(c + 3) % 4] & 0x00ff0000)
+            | (state[(c + 2) % 4] & 0x0000ff00)
+            | (state[(c + 1) % 4] & 0x000000ff);
+    }
     new_state
 }
 
 // SubBytes function
-fn sub_bytes(state: Block) -> Block {
+fn sub_bytes(state: State) -> State {
+    if cfg!(feature = "constant-time") {
+        return ct::sub_bytes(state);
+    }
     let mut new_state = state;
     for i in 0..4 {
-        for j in 0..4 {
-            new_state[i][j] = sbox(state[i][j]);
+        let mut bytes = state[i].to_be_bytes();
+        for byte in bytes.iter_mut() {
+            *byte = sbox(*byte);
         }
+        new_state[i] = u32::from_be_bytes(bytes);
+    }
+    new_state
+}
+
+// InvSubBytes function
+fn inv_sub_bytes(state: State) -> State {
+    if cfg!(feature = "constant-time") {
+        return ct::inv_sub_bytes(state);
+    }
+    let mut new_state = state;
+    for i in 0..4 {
+        let mut bytes = state[i].to_be_bytes();
+        for byte in bytes.iter_mut() {
+            *byte = inv_sbox(*byte);
+        }
+        new_state[i] = u32::from_be_bytes(bytes);
     }
     new_state
 }
 
 // SBox function
 fn sbox(byte: u8) -> u8 {
-    let sbox = [
-        0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab,
-        0x76, 0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4,
-        0x72, 0xc0, 0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71,
-        0xd8, 0x31, 0x15, 0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2,
-        0xeb, 0x27, 0xb2, 0x75, 0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6,
-        0xb3, 0x29, 0xe3, 0x2f, 0x84, 0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb,
-        0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf, 0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45,
-        0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8, 0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5,
-        0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2, 0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44,
-        0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73, 0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a,
-        0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb, 0xe0, 0x32, 0x3a, 0x0a, 0x49,
-        0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79, 0xe7, 0xc8, 0x37, 0x6d,
-        0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08, 0xba, 0x78, 0x25,
-        0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a, 0x70, 0x3e,
-        0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e, 0xe1,
-        0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
-        0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb,
-        0x16,
+    SBOX[byte as usize]
+}
+
+pub(crate) const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+// Inverse SBox function
+fn inv_sbox(byte: u8) -> u8 {
+    let inv_sbox = [
+        0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7,
+        0xfb, 0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde,
+        0xe9, 0xcb, 0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42,
+        0xfa, 0xc3, 0x4e, 0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49,
+        0x6d, 0x8b, 0xd1, 0x25, 0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c,
+        0xcc, 0x5d, 0x65, 0xb6, 0x92, 0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15,
+        0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84, 0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7,
+        0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06, 0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02,
+        0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b, 0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc,
+        0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73, 0x96, 0xac, 0x74, 0x22, 0xe7, 0xad,
+        0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e, 0x47, 0xf1, 0x1a, 0x71, 0x1d,
+        0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b, 0xfc, 0x56, 0x3e, 0x4b,
+        0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4, 0x1f, 0xdd, 0xa8,
+        0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f, 0x60, 0x51,
+        0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef, 0xa0,
+        0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+        0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c,
+        0x7d,
     ];
-    sbox[byte as usize]
+    inv_sbox[byte as usize]
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::stream_to_block;
 
     #[test]
     fn test_aes() {
@@ -208,4 +521,136 @@ mod tests {
         ];
         assert_eq!(aes(block, key), cipher);
     }
+
+    #[test]
+    fn test_aes_fips_197_appendix_c() {
+        let plaintext: Stream = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+
+        let mut key128 = [0u8; 16];
+        key128.copy_from_slice(&key[..16]);
+        let expected128: Stream = [
+            0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+            0xc5, 0x5a,
+        ];
+        assert_eq!(
+            aes(stream_to_block(plaintext), key128),
+            stream_to_block(expected128)
+        );
+
+        let mut key192 = [0u8; 24];
+        key192.copy_from_slice(&key[..24]);
+        let expected192: Stream = [
+            0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d,
+            0x71, 0x91,
+        ];
+        assert_eq!(
+            aes192(stream_to_block(plaintext), key192),
+            stream_to_block(expected192)
+        );
+
+        let expected256: Stream = [
+            0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49,
+            0x60, 0x89,
+        ];
+        assert_eq!(
+            aes256(stream_to_block(plaintext), key),
+            stream_to_block(expected256)
+        );
+    }
+
+    // FIPS-197, Appendix C, with the key schedule expanded once
+    #[test]
+    fn test_aes_cipher() {
+        let plaintext: Stream = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+
+        let cipher = Aes128::new(key[..16].try_into().unwrap());
+        let ciphertext = (&cipher).encrypt_stream(plaintext);
+        assert_eq!(ciphertext[..4], [0x69, 0xc4, 0xe0, 0xd8]);
+        assert_eq!((&cipher).decrypt_stream(ciphertext), plaintext);
+
+        let cipher = Aes192::new(key[..24].try_into().unwrap());
+        let ciphertext = (&cipher).encrypt_stream(plaintext);
+        assert_eq!(ciphertext[..4], [0xdd, 0xa9, 0x7c, 0xa4]);
+        assert_eq!((&cipher).decrypt_stream(ciphertext), plaintext);
+
+        let cipher = Aes256::new(key);
+        let ciphertext = (&cipher).encrypt_stream(plaintext);
+        assert_eq!(ciphertext, key.encrypt_stream(plaintext));
+        assert_eq!(
+            (&cipher).encrypt_block(stream_to_block(plaintext)),
+            stream_to_block(ciphertext)
+        );
+        assert_eq!((&cipher).decrypt_stream(ciphertext), plaintext);
+    }
+
+    #[test]
+    fn test_constant_time_sbox() {
+        for byte in 0..=255u8 {
+            let word = u32::from_be_bytes([byte; 4]);
+            let substituted = u32::from_be_bytes([sbox(byte); 4]);
+            let inverted = u32::from_be_bytes([inv_sbox(byte); 4]);
+            assert_eq!(ct::sub_bytes([word; 4]), [substituted; 4]);
+            assert_eq!(ct::sub_word(word), substituted);
+            assert_eq!(ct::inv_sub_bytes([word; 4]), [inverted; 4]);
+            assert_eq!(ct::inv_sbox(byte), inv_sbox(byte));
+        }
+
+        // Every lane of the bitsliced state is substituted independently
+        let mut state: State = [0; 4];
+        let mut expected: State = [0; 4];
+        for i in 0..4 {
+            let bytes: [u8; 4] = core::array::from_fn(|j| (i * 4 + j) as u8 * 17);
+            state[i] = u32::from_be_bytes(bytes);
+            expected[i] = u32::from_be_bytes(bytes.map(sbox));
+        }
+        assert_eq!(ct::sub_bytes(state), expected);
+    }
+
+    #[test]
+    fn test_constant_time_xtime() {
+        for byte in 0..=255u8 {
+            let bits = byte_to_bits(byte);
+            let mut result = [0; 8];
+            result[..7].copy_from_slice(&bits[1..]);
+            let mut expected = bits_to_byte(result);
+            if bits[0] == 1 {
+                expected ^= 0x1b;
+            }
+            assert_eq!(ct::xtime(byte), expected);
+            assert_eq!(gfmulx(byte), expected);
+        }
+    }
+
+    #[test]
+    fn test_t_table() {
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8 * 7);
+        let mut key192 = [0; 24];
+        key192.copy_from_slice(&key[..24]);
+        let mut key128 = [0; 16];
+        key128.copy_from_slice(&key[..16]);
+
+        // Chains the blocks so every byte value shows up in some round
+        let mut block: Block = [[0; 4]; 4];
+        for _ in 0..100 {
+            let expected = aes(block, key128);
+            assert_eq!(aes_t_table(block, key128), expected);
+            assert_eq!(
+                t_table::cipher(block_to_state(block), &expand_key::<52>(&key192)),
+                block_to_state(aes192(block, key192))
+            );
+            assert_eq!(
+                t_table::cipher(block_to_state(block), &expand_key::<60>(&key)),
+                block_to_state(aes256(block, key))
+            );
+            block = expected;
+        }
+    }
 }
@@ -1,48 +1,336 @@
-use crate::aes::aes;
+use crate::aes::{AesCipher, AesKey};
+use crate::error::Error;
 use crate::gctr::{gctr, increment_32};
-use crate::ghash::ghash;
-use crate::utils::{block_to_stream, stream_to_block, Block, Stream};
+use crate::ghash::ghash_padded;
+use crate::utils::{ct_eq, wipe, Stream};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
 
-// AES-GCM encryption
+// AES-GCM encryption of a single block of plaintext and AAD
 pub fn aes_gcm(key: Stream, plaintext: Stream, iv: [u8; 12], aad: Stream) -> (Stream, Stream) {
+    let mut ciphertext = plaintext;
+    let tag = aes_gcm_encrypt_in_place(key, iv, &aad, &mut ciphertext);
+    (ciphertext, tag)
+}
+
+// AES-GCM encryption of arbitrary length plaintext and AAD
+#[cfg(feature = "alloc")]
+pub fn aes_gcm_encrypt<K: AesKey>(
+    key: K,
+    iv: [u8; 12],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> (Vec<u8>, Stream) {
+    let mut ciphertext = plaintext.to_vec();
+    let tag = aes_gcm_encrypt_in_place(key, iv, aad, &mut ciphertext);
+    (ciphertext, tag)
+}
+
+// AES-GCM encryption that overwrites the plaintext in `buffer` with the ciphertext and returns
+// the tag
+pub fn aes_gcm_encrypt_in_place<K: AesKey>(
+    key: K,
+    iv: [u8; 12],
+    aad: &[u8],
+    buffer: &mut [u8],
+) -> Stream {
     // Step 1: Generate hash key as encryption of a zero block with AES
-    let zero_block: Block = [[0; 4]; 4];
-    let hashkey = block_to_stream(aes(zero_block, key));
+    let mut hashkey = key.encrypt_stream([0; 16]);
 
-    // Step 2: Generate j0 as iv || 0 ^{31} || 1, where || is concatenation
-    let mut j0 = [0; 16];
-    for i in 0..12 {
-        j0[i] = iv[i];
+    let tag = encrypt_in_place(key, &hashkey, iv, aad, buffer);
+    wipe(&mut hashkey);
+    tag
+}
+
+// AES-GCM encryption with the tag truncated to `tag_len` bytes, which is 16, 15, 14, 13 or 12,
+// or 8 or 4 for applications that meet the requirements of SP 800-38D, Appendix C
+#[cfg(feature = "alloc")]
+pub fn aes_gcm_encrypt_truncated<K: AesKey>(
+    key: K,
+    iv: [u8; 12],
+    aad: &[u8],
+    plaintext: &[u8],
+    tag_len: usize,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let mut ciphertext = plaintext.to_vec();
+    let mut tag = vec![0; tag_len];
+    aes_gcm_encrypt_in_place_truncated(key, iv, aad, &mut ciphertext, &mut tag)?;
+    Ok((ciphertext, tag))
+}
+
+// AES-GCM encryption in place that writes the tag truncated to the length of `tag`, the
+// buffer is left untouched if that length is not permitted
+pub fn aes_gcm_encrypt_in_place_truncated<K: AesKey>(
+    key: K,
+    iv: [u8; 12],
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &mut [u8],
+) -> Result<(), Error> {
+    check_tag_len(tag.len())?;
+    let full_tag = aes_gcm_encrypt_in_place(key, iv, aad, buffer);
+    tag.copy_from_slice(&full_tag[..tag.len()]);
+    Ok(())
+}
+
+// AES-GCM decryption of arbitrary length ciphertext and AAD, the plaintext is only returned if
+// the tag is valid
+#[cfg(feature = "alloc")]
+pub fn aes_gcm_decrypt<K: AesKey>(
+    key: K,
+    iv: [u8; 12],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &Stream,
+) -> Result<Vec<u8>, Error> {
+    let mut plaintext = ciphertext.to_vec();
+    aes_gcm_decrypt_in_place(key, iv, aad, &mut plaintext, tag)?;
+    Ok(plaintext)
+}
+
+// AES-GCM decryption that overwrites the ciphertext in `buffer` with the plaintext. The tag is
+// checked first, and the buffer is left untouched if it does not match.
+pub fn aes_gcm_decrypt_in_place<K: AesKey>(
+    key: K,
+    iv: [u8; 12],
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &Stream,
+) -> Result<(), Error> {
+    let mut hashkey = key.encrypt_stream([0; 16]);
+    let result = decrypt_in_place(key, &hashkey, iv, aad, buffer, tag);
+    wipe(&mut hashkey);
+    result
+}
+
+// AES-GCM decryption with a tag truncated to `tag_len` bytes. The tag length is fixed for a
+// key, so a tag of any other length is rejected rather than checked on fewer bytes.
+#[cfg(feature = "alloc")]
+pub fn aes_gcm_decrypt_truncated<K: AesKey>(
+    key: K,
+    iv: [u8; 12],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+    tag_len: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut plaintext = ciphertext.to_vec();
+    aes_gcm_decrypt_in_place_truncated(key, iv, aad, &mut plaintext, tag, tag_len)?;
+    Ok(plaintext)
+}
+
+// AES-GCM decryption in place with a tag truncated to `tag_len` bytes, the buffer is left
+// untouched if the tag does not have that length or does not match
+pub fn aes_gcm_decrypt_in_place_truncated<K: AesKey>(
+    key: K,
+    iv: [u8; 12],
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8],
+    tag_len: usize,
+) -> Result<(), Error> {
+    check_truncated_tag(tag, tag_len)?;
+    let mut hashkey = key.encrypt_stream([0; 16]);
+    let result = decrypt_in_place(key, &hashkey, iv, aad, buffer, tag);
+    wipe(&mut hashkey);
+    result
+}
+
+/// An AES-GCM key, which keeps the expanded AES key and the hash key derived from it for all
+/// the messages it encrypts.
+///
+/// With the `zeroize` feature, the round keys and the hash key are wiped when it is dropped.
+#[derive(Clone)]
+pub struct AesGcm<const W: usize> {
+    cipher: AesCipher<W>,
+    hash_key: Stream,
+}
+
+impl<const W: usize> AesGcm<W> {
+    pub fn new(cipher: AesCipher<W>) -> Self {
+        let hash_key = (&cipher).encrypt_stream([0; 16]);
+        Self { cipher, hash_key }
+    }
+
+    /// Encrypts arbitrary length plaintext and AAD, returns the ciphertext and the tag.
+    #[cfg(feature = "alloc")]
+    pub fn encrypt(&self, iv: [u8; 12], aad: &[u8], plaintext: &[u8]) -> (Vec<u8>, Stream) {
+        let mut ciphertext = plaintext.to_vec();
+        let tag = self.encrypt_in_place_detached(iv, aad, &mut ciphertext);
+        (ciphertext, tag)
+    }
+
+    /// Overwrites the plaintext in `buffer` with the ciphertext, and returns the tag separately.
+    pub fn encrypt_in_place_detached(&self, iv: [u8; 12], aad: &[u8], buffer: &mut [u8]) -> Stream {
+        encrypt_in_place(&self.cipher, &self.hash_key, iv, aad, buffer)
     }
-    j0[15] = 1; // Final byte of j0 is set to 1
-    let j0_block = stream_to_block(j0);
+
+    /// Same as [`encrypt_in_place_detached`](Self::encrypt_in_place_detached), but writes the
+    /// tag truncated to the length of `tag`, which is 16, 15, 14, 13 or 12 bytes, or 8 or 4
+    /// for applications that meet the requirements of SP 800-38D, Appendix C.
+    pub fn encrypt_in_place_truncated(
+        &self,
+        iv: [u8; 12],
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &mut [u8],
+    ) -> Result<(), Error> {
+        check_tag_len(tag.len())?;
+        let full_tag = self.encrypt_in_place_detached(iv, aad, buffer);
+        tag.copy_from_slice(&full_tag[..tag.len()]);
+        Ok(())
+    }
+
+    /// Decrypts arbitrary length ciphertext and AAD, the plaintext is only returned if the tag
+    /// is valid.
+    #[cfg(feature = "alloc")]
+    pub fn decrypt(
+        &self,
+        iv: [u8; 12],
+        aad: &[u8],
+        ciphertext: &[u8],
+        tag: &Stream,
+    ) -> Result<Vec<u8>, Error> {
+        let mut plaintext = ciphertext.to_vec();
+        self.decrypt_in_place_detached(iv, aad, &mut plaintext, tag)?;
+        Ok(plaintext)
+    }
+
+    /// Overwrites the ciphertext in `buffer` with the plaintext if the tag is valid, and leaves
+    /// it untouched otherwise.
+    pub fn decrypt_in_place_detached(
+        &self,
+        iv: [u8; 12],
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &Stream,
+    ) -> Result<(), Error> {
+        decrypt_in_place(&self.cipher, &self.hash_key, iv, aad, buffer, tag)
+    }
+
+    /// Same as [`decrypt_in_place_detached`](Self::decrypt_in_place_detached), but with a tag
+    /// truncated to `tag_len` bytes. A tag of any other length is rejected, so that the length
+    /// used with a key cannot be lowered by whoever supplies the tag.
+    pub fn decrypt_in_place_truncated(
+        &self,
+        iv: [u8; 12],
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+        tag_len: usize,
+    ) -> Result<(), Error> {
+        check_truncated_tag(tag, tag_len)?;
+        decrypt_in_place(&self.cipher, &self.hash_key, iv, aad, buffer, tag)
+    }
+}
+
+impl<const W: usize> Drop for AesGcm<W> {
+    fn drop(&mut self) {
+        wipe(&mut self.hash_key);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const W: usize> zeroize::ZeroizeOnDrop for AesGcm<W> {}
+
+// Steps 2 to 5 of AES-GCM encryption, given the hash key
+fn encrypt_in_place<K: AesKey>(
+    key: K,
+    hashkey: &Stream,
+    iv: [u8; 12],
+    aad: &[u8],
+    buffer: &mut [u8],
+) -> Stream {
+    // Step 2: Generate j0 as iv || 0 ^{31} || 1, where || is concatenation
+    let mut j0 = j0(iv);
 
     // Step 3: Perform GCTR on the incremented J0 and the plaintext
-    let ciphertext = gctr(key, increment_32(j0_block), plaintext);
+    gctr(key, increment_32(j0), buffer);
 
-    // Step 4: Construct block S for GHASH
-    let mut s_blocks = [[0u8; 16]; 3];
-    s_blocks[0] = aad; // First block is AAD
-    s_blocks[1] = ciphertext; // Second block is ciphertext
-    s_blocks[2] = [
-        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-        0x80,
-    ]; // Padding and length encoding
+    let tag = compute_tag(key, hashkey, j0, aad, buffer);
+    wipe(&mut j0);
+    tag
+}
 
-    // GHASH computation
-    let s_blocks_array: [[u8; 16]; 3] = [s_blocks[0], s_blocks[1], s_blocks[2]];
-    let ghash_result = ghash(hashkey, s_blocks_array);
+// AES-GCM decryption given the hash key, which recomputes the tag over the ciphertext before
+// decrypting it. The length of `tag` has been checked by the caller.
+fn decrypt_in_place<K: AesKey>(
+    key: K,
+    hashkey: &Stream,
+    iv: [u8; 12],
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<(), Error> {
+    let mut j0 = j0(iv);
+    let mut expected_tag = compute_tag(key, hashkey, j0, aad, buffer);
+    let result = if ct_eq(&expected_tag[..tag.len()], tag) {
+        gctr(key, increment_32(j0), buffer);
+        Ok(())
+    } else {
+        Err(Error::AuthenticationFailed)
+    };
+    wipe(&mut expected_tag);
+    wipe(&mut j0);
+    result
+}
 
-    // Step 5: Calculate the authentication tag T using GCTR on J0 and GHASH result
-    let tag = gctr(key, j0_block, ghash_result);
+// Tag lengths permitted by SP 800-38D, 5.2.1.2. The 64 and 32-bit tags bound the lengths of the
+// inputs and the number of decryptions under a key, see its Appendix C, which is left to the
+// applications that use them.
+fn check_tag_len(tag_len: usize) -> Result<(), Error> {
+    match tag_len {
+        4 | 8 | 12..=16 => Ok(()),
+        _ => Err(Error::InvalidTagLength),
+    }
+}
 
-    (ciphertext, tag)
+// A truncated tag has to be of the permitted length the caller expects
+fn check_truncated_tag(tag: &[u8], tag_len: usize) -> Result<(), Error> {
+    check_tag_len(tag_len)?;
+    if tag.len() != tag_len {
+        return Err(Error::InvalidTagLength);
+    }
+    Ok(())
+}
+
+// Pre-counter block for a 96-bit IV, the IV followed by a 32-bit counter starting at 1
+fn j0(iv: [u8; 12]) -> Stream {
+    let mut j0 = [0; 16];
+    j0[..12].copy_from_slice(&iv);
+    j0[15] = 1; // Final byte of j0 is set to 1
+    j0
+}
+
+// Steps 4 and 5 of AES-GCM, the tag over the AAD and the ciphertext
+fn compute_tag<K: AesKey>(
+    key: K,
+    hashkey: &Stream,
+    j0: Stream,
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Stream {
+    // Step 4: GHASH over the zero padded AAD and ciphertext followed by their
+    // lengths in bits
+    let mut lengths: Stream = [0; 16];
+    lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+    lengths[8..].copy_from_slice(&(ciphertext.len() as u64 * 8).to_be_bytes());
+    let ghash_result = ghash_padded(*hashkey, [0; 16], aad);
+    let ghash_result = ghash_padded(*hashkey, ghash_result, ciphertext);
+    let ghash_result = ghash_padded(*hashkey, ghash_result, &lengths);
+
+    // Step 5: Calculate the authentication tag T using GCTR on J0 and GHASH result
+    let mut tag = ghash_result;
+    gctr(key, j0, &mut tag);
+    tag
 }
 
 // Test cases for AES-GCM
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aes::Aes128;
+    use crate::utils::hex_to_bytes;
 
     #[test]
     fn test_aes_gcm() {
@@ -76,4 +364,534 @@ mod tests {
         let (ciphertext, _tag) = aes_gcm(key, msg, iv, aad);
         assert_eq!(ciphertext, expected_ciphertext);
     }
+
+    // Test case 4 of the GCM specification
+    #[test]
+    fn test_aes_gcm_encrypt_in_place() {
+        let key: [u8; 16] = hex_to_bytes("feffe9928665731c6d6a8f9467308308")
+            .try_into()
+            .unwrap();
+        let iv: [u8; 12] = hex_to_bytes("cafebabefacedbaddecaf888").try_into().unwrap();
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plaintext = hex_to_bytes(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        );
+        let ciphertext = hex_to_bytes(
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+             21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+        );
+        let tag = hex_to_bytes("5bc94fbc3221a5db94fae95ae7121a47");
+
+        let mut buffer = plaintext.clone();
+        assert_eq!(
+            aes_gcm_encrypt_in_place(key, iv, &aad, &mut buffer).to_vec(),
+            tag
+        );
+        assert_eq!(buffer, ciphertext);
+
+        let mut buffer = plaintext;
+        let key = AesGcm::new(Aes128::new(key));
+        assert_eq!(
+            key.encrypt_in_place_detached(iv, &aad, &mut buffer)
+                .to_vec(),
+            tag
+        );
+        assert_eq!(buffer, ciphertext);
+    }
+
+    // Test case 4 of the GCM specification, and forgeries of it
+    #[test]
+    fn test_aes_gcm_decrypt_in_place() {
+        let key: [u8; 16] = hex_to_bytes("feffe9928665731c6d6a8f9467308308")
+            .try_into()
+            .unwrap();
+        let iv: [u8; 12] = hex_to_bytes("cafebabefacedbaddecaf888").try_into().unwrap();
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plaintext = hex_to_bytes(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        );
+        let ciphertext = hex_to_bytes(
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+             21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+        );
+        let tag: Stream = hex_to_bytes("5bc94fbc3221a5db94fae95ae7121a47")
+            .try_into()
+            .unwrap();
+
+        let mut buffer = ciphertext.clone();
+        assert_eq!(
+            aes_gcm_decrypt_in_place(key, iv, &aad, &mut buffer, &tag),
+            Ok(())
+        );
+        assert_eq!(buffer, plaintext);
+
+        let key = AesGcm::new(Aes128::new(key));
+        let mut buffer = ciphertext.clone();
+        assert_eq!(
+            key.decrypt_in_place_detached(iv, &aad, &mut buffer, &tag),
+            Ok(())
+        );
+        assert_eq!(buffer, plaintext);
+
+        // A forged tag, AAD or ciphertext leaves the buffer as it is
+        let mut forged_tag = tag;
+        forged_tag[15] ^= 1;
+        let mut buffer = ciphertext.clone();
+        assert_eq!(
+            key.decrypt_in_place_detached(iv, &aad, &mut buffer, &forged_tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(buffer, ciphertext);
+        assert_eq!(
+            key.decrypt_in_place_detached(iv, &aad[1..], &mut buffer, &tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(buffer, ciphertext);
+        buffer[0] ^= 1;
+        assert_eq!(
+            key.decrypt_in_place_detached(iv, &aad, &mut buffer, &tag),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    // A payload spanning many blocks and ending in a partial one, encrypted and decrypted in
+    // the same buffer
+    #[test]
+    fn test_aes_gcm_in_place_roundtrip() {
+        let key = AesGcm::new(Aes128::new([0x42; 16]));
+        let iv = [0x24; 12];
+        let aad = [0x5a; 20];
+        let plaintext: [u8; 4103] = core::array::from_fn(|i| (i * 7) as u8);
+
+        let mut buffer = plaintext;
+        let tag = key.encrypt_in_place_detached(iv, &aad, &mut buffer);
+        assert_ne!(buffer, plaintext);
+        let mut expected = plaintext;
+        assert_eq!(
+            aes_gcm_encrypt_in_place([0x42u8; 16], iv, &aad, &mut expected),
+            tag
+        );
+        assert_eq!(buffer, expected);
+
+        assert_eq!(
+            key.decrypt_in_place_detached(iv, &aad, &mut buffer, &tag),
+            Ok(())
+        );
+        assert_eq!(buffer, plaintext);
+    }
+
+    // NIST CAVP gcmEncryptExtIV128.rsp with Tlen = 128, and test case 4 of the GCM
+    // specification, truncated to every supported tag length to cover the API. The CAVP
+    // vectors with truncated tags are in `test_aes_gcm_cavp_truncated_tags`.
+    #[test]
+    fn test_aes_gcm_truncated_tags() {
+        let vectors = [
+            (
+                "c939cc13397c1d37de6ae0e1cb7c423c",
+                "b3d8cc017cbb89b39e0f67e2",
+                "24825602bd12a984e0092d3e448eda5f",
+                "c3b3c41f113a31b73d9a5cd432103069",
+                "93fe7d9e9bfd10348a5606e5cafa7354",
+                "0032a1dc85f1c9786925a2e71d8272dd",
+            ),
+            (
+                "feffe9928665731c6d6a8f9467308308",
+                "cafebabefacedbaddecaf888",
+                "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+                "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+                 1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+                 21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+                "5bc94fbc3221a5db94fae95ae7121a47",
+            ),
+        ];
+        for (key, iv, aad, plaintext, ciphertext, tag) in vectors {
+            let key: [u8; 16] = hex_to_bytes(key).try_into().unwrap();
+            let iv: [u8; 12] = hex_to_bytes(iv).try_into().unwrap();
+            let aad = hex_to_bytes(aad);
+            let plaintext = hex_to_bytes(plaintext);
+            let ciphertext = hex_to_bytes(ciphertext);
+            let tag = hex_to_bytes(tag);
+            let aes_gcm = AesGcm::new(Aes128::new(key));
+
+            for tag_len in [16, 15, 14, 13, 12, 8, 4] {
+                let mut buffer = plaintext.clone();
+                let mut truncated_tag = [0; 16];
+                assert_eq!(
+                    aes_gcm_encrypt_in_place_truncated(
+                        key,
+                        iv,
+                        &aad,
+                        &mut buffer,
+                        &mut truncated_tag[..tag_len]
+                    ),
+                    Ok(())
+                );
+                assert_eq!(buffer, ciphertext);
+                assert_eq!(truncated_tag[..tag_len], tag[..tag_len]);
+
+                let mut buffer = plaintext.clone();
+                let mut truncated_tag = [0; 16];
+                assert_eq!(
+                    aes_gcm.encrypt_in_place_truncated(
+                        iv,
+                        &aad,
+                        &mut buffer,
+                        &mut truncated_tag[..tag_len]
+                    ),
+                    Ok(())
+                );
+                assert_eq!(truncated_tag[..tag_len], tag[..tag_len]);
+
+                assert_eq!(
+                    aes_gcm_decrypt_in_place_truncated(
+                        key,
+                        iv,
+                        &aad,
+                        &mut buffer,
+                        &tag[..tag_len],
+                        tag_len
+                    ),
+                    Ok(())
+                );
+                assert_eq!(buffer, plaintext);
+
+                let mut buffer = ciphertext.clone();
+                let mut forged_tag = tag.clone();
+                forged_tag[tag_len - 1] ^= 1;
+                assert_eq!(
+                    aes_gcm.decrypt_in_place_truncated(
+                        iv,
+                        &aad,
+                        &mut buffer,
+                        &forged_tag[..tag_len],
+                        tag_len
+                    ),
+                    Err(Error::AuthenticationFailed)
+                );
+                assert_eq!(buffer, ciphertext);
+
+                // A valid but shorter prefix of the tag is not accepted in place of the full
+                // length expected for the key
+                if tag_len > 4 {
+                    assert_eq!(
+                        aes_gcm.decrypt_in_place_truncated(
+                            iv,
+                            &aad,
+                            &mut buffer,
+                            &tag[..4],
+                            tag_len
+                        ),
+                        Err(Error::InvalidTagLength)
+                    );
+                    assert_eq!(buffer, ciphertext);
+                }
+            }
+
+            // Neither encryption nor decryption takes other tag lengths
+            for tag_len in [0, 1, 2, 3, 5, 6, 7, 9, 10, 11] {
+                let mut buffer = plaintext.clone();
+                assert_eq!(
+                    aes_gcm.encrypt_in_place_truncated(
+                        iv,
+                        &aad,
+                        &mut buffer,
+                        &mut [0; 16][..tag_len]
+                    ),
+                    Err(Error::InvalidTagLength)
+                );
+                assert_eq!(buffer, plaintext);
+
+                let mut buffer = ciphertext.clone();
+                assert_eq!(
+                    aes_gcm_decrypt_in_place_truncated(
+                        key,
+                        iv,
+                        &aad,
+                        &mut buffer,
+                        &tag[..tag_len],
+                        tag_len
+                    ),
+                    Err(Error::InvalidTagLength)
+                );
+                assert_eq!(buffer, ciphertext);
+            }
+            assert_eq!(
+                aes_gcm.encrypt_in_place_truncated(iv, &aad, &mut [], &mut [0; 17]),
+                Err(Error::InvalidTagLength)
+            );
+        }
+    }
+
+    // NIST CAVP gcmEncryptExtIV128.rsp, Keylen = 128, IVlen = 96, PTlen = 0, AADlen = 0 and
+    // count 0 of Taglen = 120, 112 and 104
+    #[test]
+    fn test_aes_gcm_cavp_truncated_tags() {
+        let vectors = [
+            (
+                "272f16edb81a7abbea887357a58c1917",
+                "794ec588176c703d3d2a7a07",
+                "b6e6f197168f5049aeda32dafbdaeb",
+            ),
+            (
+                "81b6844aab6a568c4556a2eb7eae752f",
+                "ce600f59618315a6829bef4d",
+                "89b43e9dbc1b4f597dbbc7655bb5",
+            ),
+            (
+                "cde2f9a9b1a004165ef9dc981f18651b",
+                "29512c29566c7322e1e33e8e",
+                "2e58ce7dabd107c82759c66a75",
+            ),
+        ];
+        for (key, iv, tag) in vectors {
+            let key: [u8; 16] = hex_to_bytes(key).try_into().unwrap();
+            let iv: [u8; 12] = hex_to_bytes(iv).try_into().unwrap();
+            let tag = hex_to_bytes(tag);
+            let tag_len = tag.len();
+
+            let mut truncated_tag = [0; 16];
+            assert_eq!(
+                aes_gcm_encrypt_in_place_truncated(
+                    key,
+                    iv,
+                    &[],
+                    &mut [],
+                    &mut truncated_tag[..tag_len]
+                ),
+                Ok(())
+            );
+            assert_eq!(truncated_tag[..tag_len], tag);
+            assert_eq!(
+                aes_gcm_decrypt_in_place_truncated(key, iv, &[], &mut [], &tag, tag_len),
+                Ok(())
+            );
+
+            // The same tag with its last bit flipped has to FAIL
+            let mut forged_tag = tag.clone();
+            forged_tag[tag_len - 1] ^= 1;
+            assert_eq!(
+                aes_gcm_decrypt_in_place_truncated(key, iv, &[], &mut [], &forged_tag, tag_len),
+                Err(Error::AuthenticationFailed)
+            );
+        }
+    }
+
+    // NIST CAVP gcmDecrypt128.rsp, Keylen = 128, IVlen = 96, PTlen = 0, AADlen = 0 and
+    // Taglen = 128, counts 0 and 1, the second of which is expected to FAIL
+    #[test]
+    fn test_aes_gcm_cavp_decrypt() {
+        let vectors = [
+            (
+                "cf063a34d4a9a76c2c86787d3f96db71",
+                "113b9785971864c83b01c787",
+                "72ac8493e3a5228b5d130a69d2510e42",
+                Ok(()),
+            ),
+            (
+                "a49a5e26a2f8cb63d05546c2a62f5343",
+                "907763b19b9b4ab6bd4f0281",
+                "a2be08210d8c470a8df6e8fbd79ec5cf",
+                Err(Error::AuthenticationFailed),
+            ),
+        ];
+        for (key, iv, tag, expected) in vectors {
+            let key: [u8; 16] = hex_to_bytes(key).try_into().unwrap();
+            let iv: [u8; 12] = hex_to_bytes(iv).try_into().unwrap();
+            let tag: Stream = hex_to_bytes(tag).try_into().unwrap();
+            assert_eq!(
+                aes_gcm_decrypt_in_place(key, iv, &[], &mut [], &tag),
+                expected
+            );
+            assert_eq!(
+                aes_gcm_decrypt_in_place_truncated(key, iv, &[], &mut [], &tag, 16),
+                expected
+            );
+        }
+    }
+
+    // Test case 4 of the GCM specification with a 96-bit tag
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_aes_gcm_encrypt_truncated() {
+        let key: [u8; 16] = hex_to_bytes("feffe9928665731c6d6a8f9467308308")
+            .try_into()
+            .unwrap();
+        let iv: [u8; 12] = hex_to_bytes("cafebabefacedbaddecaf888").try_into().unwrap();
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plaintext = hex_to_bytes(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        );
+        let (ciphertext, tag) = aes_gcm_encrypt_truncated(key, iv, &aad, &plaintext, 12).unwrap();
+        assert_eq!(tag, hex_to_bytes("5bc94fbc3221a5db94fae95a"));
+        assert_eq!(
+            aes_gcm_decrypt_truncated(key, iv, &aad, &ciphertext, &tag, 12),
+            Ok(plaintext.clone())
+        );
+        assert_eq!(
+            aes_gcm_decrypt_truncated(key, iv, &aad, &ciphertext, &tag[..8], 12),
+            Err(Error::InvalidTagLength)
+        );
+        assert_eq!(
+            aes_gcm_encrypt_truncated(key, iv, &aad, &plaintext, 10),
+            Err(Error::InvalidTagLength)
+        );
+    }
+
+    // Test cases 1 to 4 of the GCM specification, AES-128
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_aes_gcm_encrypt() {
+        let (ciphertext, tag) = aes_gcm_encrypt([0u8; 16], [0u8; 12], &[], &[]);
+        assert!(ciphertext.is_empty());
+        assert_eq!(
+            tag.to_vec(),
+            hex_to_bytes("58e2fccefa7e3061367f1d57a4e7455a")
+        );
+
+        let (ciphertext, tag) = aes_gcm_encrypt([0u8; 16], [0u8; 12], &[], &[0u8; 16]);
+        assert_eq!(ciphertext, hex_to_bytes("0388dace60b6a392f328c2b971b2fe78"));
+        assert_eq!(
+            tag.to_vec(),
+            hex_to_bytes("ab6e47d42cec13bdf53a67b21257bddf")
+        );
+
+        let key: [u8; 16] = hex_to_bytes("feffe9928665731c6d6a8f9467308308")
+            .try_into()
+            .unwrap();
+        let iv: [u8; 12] = hex_to_bytes("cafebabefacedbaddecaf888").try_into().unwrap();
+        let plaintext = hex_to_bytes(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+        );
+        let (ciphertext, tag) = aes_gcm_encrypt(key, iv, &[], &plaintext);
+        assert_eq!(
+            ciphertext,
+            hex_to_bytes(
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+                 21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985"
+            )
+        );
+        assert_eq!(
+            tag.to_vec(),
+            hex_to_bytes("4d5c2af327cd64a62cf35abd2ba6fab4")
+        );
+
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let (ciphertext, tag) = aes_gcm_encrypt(key, iv, &aad, &plaintext[..60]);
+        assert_eq!(
+            ciphertext,
+            hex_to_bytes(
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+                 21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091"
+            )
+        );
+        assert_eq!(
+            tag.to_vec(),
+            hex_to_bytes("5bc94fbc3221a5db94fae95ae7121a47")
+        );
+    }
+
+    // Test cases 10 and 16 of the GCM specification, AES-192 and AES-256
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_aes_gcm_encrypt_key_sizes() {
+        let iv: [u8; 12] = hex_to_bytes("cafebabefacedbaddecaf888").try_into().unwrap();
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plaintext = hex_to_bytes(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        );
+
+        let key: [u8; 24] = hex_to_bytes("feffe9928665731c6d6a8f9467308308feffe9928665731c")
+            .try_into()
+            .unwrap();
+        let (ciphertext, tag) = aes_gcm_encrypt(key, iv, &aad, &plaintext);
+        assert_eq!(
+            ciphertext,
+            hex_to_bytes(
+                "3980ca0b3c00e841eb06fac4872a2757859e1ceaa6efd984628593b40ca1e19c\
+                 7d773d00c144c525ac619d18c84a3f4718e2448b2fe324d9ccda2710"
+            )
+        );
+        assert_eq!(
+            tag.to_vec(),
+            hex_to_bytes("2519498e80f1478f37ba55bd6d27618c")
+        );
+
+        let key: [u8; 32] =
+            hex_to_bytes("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308")
+                .try_into()
+                .unwrap();
+        let (ciphertext, tag) = aes_gcm_encrypt(key, iv, &aad, &plaintext);
+        assert_eq!(
+            ciphertext,
+            hex_to_bytes(
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+                 8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662"
+            )
+        );
+        assert_eq!(
+            tag.to_vec(),
+            hex_to_bytes("76fc6ece0f4e1768cddf8853bb2d551b")
+        );
+    }
+
+    // Test cases 4 and 16 of the GCM specification, encrypting and decrypting under the same key
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_aes_gcm_key() {
+        use crate::aes::Aes256;
+
+        let iv: [u8; 12] = hex_to_bytes("cafebabefacedbaddecaf888").try_into().unwrap();
+        let aad = hex_to_bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plaintext = hex_to_bytes(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        );
+
+        let key_bytes: [u8; 16] = hex_to_bytes("feffe9928665731c6d6a8f9467308308")
+            .try_into()
+            .unwrap();
+        let key = AesGcm::new(Aes128::new(key_bytes));
+        let (ciphertext, tag) = key.encrypt(iv, &aad, &plaintext);
+        assert_eq!(
+            tag.to_vec(),
+            hex_to_bytes("5bc94fbc3221a5db94fae95ae7121a47")
+        );
+        assert_eq!(key.encrypt(iv, &aad, &plaintext), (ciphertext.clone(), tag));
+        assert_eq!(
+            key.decrypt(iv, &aad, &ciphertext, &tag),
+            Ok(plaintext.clone())
+        );
+        assert_eq!(
+            aes_gcm_decrypt(key_bytes, iv, &aad, &ciphertext, &tag),
+            Ok(plaintext.clone())
+        );
+        assert_eq!(
+            key.decrypt(iv, &[], &ciphertext, &tag),
+            Err(Error::AuthenticationFailed)
+        );
+
+        let key = AesGcm::new(Aes256::new(
+            hex_to_bytes("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308")
+                .try_into()
+                .unwrap(),
+        ));
+        let (ciphertext, tag) = key.encrypt(iv, &aad, &plaintext);
+        assert_eq!(
+            ciphertext,
+            hex_to_bytes(
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+                 8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662"
+            )
+        );
+        assert_eq!(
+            tag.to_vec(),
+            hex_to_bytes("76fc6ece0f4e1768cddf8853bb2d551b")
+        );
+    }
 }
@@ -0,0 +1,216 @@
+use crate::aes::AesKey;
+use crate::error::Error;
+use crate::utils::{stream_xor, Stream};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+// CBC encryption, every plaintext block is XORed with the previous ciphertext block,
+// or the IV for the first one, before being encrypted
+#[cfg(feature = "alloc")]
+pub fn cbc_encrypt<K: AesKey>(key: K, iv: Stream, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut ciphertext = plaintext.to_vec();
+    cbc_encrypt_in_place(key, iv, &mut ciphertext)?;
+    Ok(ciphertext)
+}
+
+// CBC decryption
+#[cfg(feature = "alloc")]
+pub fn cbc_decrypt<K: AesKey>(key: K, iv: Stream, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut plaintext = ciphertext.to_vec();
+    cbc_decrypt_in_place(key, iv, &mut plaintext)?;
+    Ok(plaintext)
+}
+
+// CBC encryption of `buffer` in place
+pub fn cbc_encrypt_in_place<K: AesKey>(key: K, iv: Stream, buffer: &mut [u8]) -> Result<(), Error> {
+    if buffer.len() % 16 != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut previous = iv;
+    for chunk in buffer.chunks_mut(16) {
+        let input = stream_xor(chunk.try_into().unwrap(), previous);
+        previous = key.encrypt_stream(input);
+        chunk.copy_from_slice(&previous);
+    }
+    Ok(())
+}
+
+// CBC decryption of `buffer` in place
+pub fn cbc_decrypt_in_place<K: AesKey>(key: K, iv: Stream, buffer: &mut [u8]) -> Result<(), Error> {
+    if buffer.len() % 16 != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut previous = iv;
+    for chunk in buffer.chunks_mut(16) {
+        let block: Stream = chunk.try_into().unwrap();
+        let output = key.decrypt_stream(block);
+        chunk.copy_from_slice(&stream_xor(output, previous));
+        previous = block;
+    }
+    Ok(())
+}
+
+// CBC encryption of a plaintext of any length, padded with PKCS#7
+#[cfg(feature = "alloc")]
+pub fn cbc_encrypt_pkcs7<K: AesKey>(key: K, iv: Stream, plaintext: &[u8]) -> Vec<u8> {
+    cbc_encrypt(key, iv, &pkcs7_pad(plaintext)).expect("Padded plaintext is block aligned")
+}
+
+// CBC decryption followed by removal of the PKCS#7 padding
+#[cfg(feature = "alloc")]
+pub fn cbc_decrypt_pkcs7<K: AesKey>(
+    key: K,
+    iv: Stream,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut plaintext = cbc_decrypt(key, iv, ciphertext)?;
+    let unpadded_len = pkcs7_unpad(&plaintext)?.len();
+    plaintext.truncate(unpadded_len);
+    Ok(plaintext)
+}
+
+// PKCS#7 padding appends n bytes of value n, with 1 <= n <= 16, up to the next block boundary
+#[cfg(feature = "alloc")]
+pub fn pkcs7_pad(data: &[u8]) -> Vec<u8> {
+    let padding = 16 - data.len() % 16;
+    let mut padded = Vec::with_capacity(data.len() + padding);
+    padded.extend_from_slice(data);
+    padded.resize(data.len() + padding, padding as u8);
+    padded
+}
+
+// Strips PKCS#7 padding, checking every padding byte
+pub fn pkcs7_unpad(data: &[u8]) -> Result<&[u8], Error> {
+    if data.is_empty() || data.len() % 16 != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    let padding = data[data.len() - 1] as usize;
+    if padding == 0 || padding > 16 {
+        return Err(Error::InvalidPadding);
+    }
+    let (unpadded, padding_bytes) = data.split_at(data.len() - padding);
+    if padding_bytes.iter().any(|&byte| byte as usize != padding) {
+        return Err(Error::InvalidPadding);
+    }
+    Ok(unpadded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    // SP 800-38A, F.2
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_cbc() {
+        let iv: Stream = hex_to_bytes("000102030405060708090a0b0c0d0e0f")
+            .try_into()
+            .unwrap();
+        let plaintext = hex_to_bytes(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        );
+
+        let key: [u8; 16] = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+            .try_into()
+            .unwrap();
+        let ciphertext = hex_to_bytes(
+            "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2\
+             73bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7",
+        );
+        assert_eq!(cbc_encrypt(key, iv, &plaintext), Ok(ciphertext.clone()));
+        assert_eq!(cbc_decrypt(key, iv, &ciphertext), Ok(plaintext.clone()));
+
+        let key: [u8; 24] = hex_to_bytes("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b")
+            .try_into()
+            .unwrap();
+        let ciphertext = hex_to_bytes(
+            "4f021db243bc633d7178183a9fa071e8b4d9ada9ad7dedf4e5e738763f69145a\
+             571b242012fb7ae07fa9baac3df102e008b0e27988598881d920a9e64f5615cd",
+        );
+        assert_eq!(cbc_encrypt(key, iv, &plaintext), Ok(ciphertext.clone()));
+        assert_eq!(cbc_decrypt(key, iv, &ciphertext), Ok(plaintext.clone()));
+
+        let key: [u8; 32] =
+            hex_to_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .try_into()
+                .unwrap();
+        let ciphertext = hex_to_bytes(
+            "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d\
+             39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b",
+        );
+        assert_eq!(cbc_encrypt(key, iv, &plaintext), Ok(ciphertext.clone()));
+        assert_eq!(cbc_decrypt(key, iv, &ciphertext), Ok(plaintext));
+    }
+
+    #[test]
+    fn test_cbc_in_place() {
+        let iv: Stream = hex_to_bytes("000102030405060708090a0b0c0d0e0f")
+            .try_into()
+            .unwrap();
+        let key: [u8; 16] = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+            .try_into()
+            .unwrap();
+        let mut buffer =
+            hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        assert_eq!(cbc_encrypt_in_place(key, iv, &mut buffer), Ok(()));
+        assert_eq!(
+            buffer,
+            hex_to_bytes("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2")
+        );
+        assert_eq!(cbc_decrypt_in_place(key, iv, &mut buffer), Ok(()));
+        assert_eq!(
+            buffer,
+            hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
+        );
+        assert_eq!(
+            cbc_decrypt_in_place(key, iv, &mut [0; 17]),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_cbc_pkcs7() {
+        let key = [0x2bu8; 16];
+        let iv = [0x00u8; 16];
+        for len in [0, 1, 15, 16, 17, 32] {
+            let plaintext: Vec<u8> = (0..len as u8).collect();
+            let ciphertext = cbc_encrypt_pkcs7(key, iv, &plaintext);
+            assert_eq!(ciphertext.len(), (len / 16 + 1) * 16);
+            assert_eq!(cbc_decrypt_pkcs7(key, iv, &ciphertext), Ok(plaintext));
+        }
+
+        assert_eq!(
+            cbc_decrypt_pkcs7(key, iv, &[0x00; 15]),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_pkcs7() {
+        assert_eq!(pkcs7_pad(&[]), vec![0x10; 16]);
+        assert_eq!(
+            pkcs7_pad(&[0xaa; 13]),
+            hex_to_bytes("aaaaaaaaaaaaaaaaaaaaaaaaaa030303")
+        );
+
+        assert_eq!(pkcs7_unpad(&[0x10; 16]), Ok(&[][..]));
+        assert_eq!(
+            pkcs7_unpad(&hex_to_bytes("aaaaaaaaaaaaaaaaaaaaaaaaaa030303")),
+            Ok(&[0xaa; 13][..])
+        );
+        assert_eq!(pkcs7_unpad(&[]), Err(Error::InvalidLength));
+        assert_eq!(pkcs7_unpad(&[0x00; 16]), Err(Error::InvalidPadding));
+        assert_eq!(pkcs7_unpad(&[0x11; 16]), Err(Error::InvalidPadding));
+        assert_eq!(
+            pkcs7_unpad(&hex_to_bytes("aaaaaaaaaaaaaaaaaaaaaaaaaa020303")),
+            Err(Error::InvalidPadding)
+        );
+    }
+}
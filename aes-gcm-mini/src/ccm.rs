@@ -0,0 +1,539 @@
+use crate::aes::AesKey;
+use crate::ctr::ctr_in_place;
+use crate::error::Error;
+use crate::utils::{ct_eq, stream_xor, Stream};
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+// AES-CCM encryption (SP 800-38C, RFC 3610) of arbitrary length plaintext and AAD, returns the
+// ciphertext and the tag. The nonce is 7 to 13 bytes long, which leaves 8 to 2 bytes to encode
+// the length of the plaintext, and the tag is 4, 6, 8, 10, 12, 14 or 16 bytes long.
+#[cfg(feature = "alloc")]
+pub fn ccm_encrypt<K: AesKey>(
+    key: K,
+    nonce: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+    tag_len: usize,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    check_parameters(nonce, plaintext, tag_len)?;
+    let mut ciphertext = plaintext.to_vec();
+    let mut tag = vec![0; tag_len];
+    ccm_encrypt_in_place(key, nonce, aad, &mut ciphertext, &mut tag)?;
+    Ok((ciphertext, tag))
+}
+
+// AES-CCM decryption, the plaintext is only returned if the tag is valid
+#[cfg(feature = "alloc")]
+pub fn ccm_decrypt<K: AesKey>(
+    key: K,
+    nonce: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut plaintext = ciphertext.to_vec();
+    ccm_decrypt_in_place(key, nonce, aad, &mut plaintext, tag)?;
+    Ok(plaintext)
+}
+
+// AES-CCM encryption of `buffer` in place, the tag takes the length of `tag`. The buffer is
+// left untouched if the parameters are invalid.
+pub fn ccm_encrypt_in_place<K: AesKey>(
+    key: K,
+    nonce: &[u8],
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &mut [u8],
+) -> Result<(), Error> {
+    check_parameters(nonce, buffer, tag.len())?;
+
+    // Step 1: Authenticate the AAD and plaintext with CBC-MAC
+    let mac = cbc_mac(key, nonce, aad, buffer, tag.len());
+
+    // Step 2: Encrypt the plaintext with the counter blocks starting at 1, the first one
+    // encrypts the tag
+    let mut initial_counter_block = counter_block(nonce);
+    let s_0 = key.encrypt_stream(initial_counter_block);
+    initial_counter_block[15] = 1;
+    ctr_in_place(key, initial_counter_block, counter_bits(nonce), buffer)?;
+
+    for (i, byte) in tag.iter_mut().enumerate() {
+        *byte = mac[i] ^ s_0[i];
+    }
+    Ok(())
+}
+
+// AES-CCM decryption of `buffer` in place. CCM authenticates the plaintext, so the buffer has
+// to be decrypted before the tag can be checked, and it is encrypted back if the tag is invalid.
+pub fn ccm_decrypt_in_place<K: AesKey>(
+    key: K,
+    nonce: &[u8],
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<(), Error> {
+    check_parameters(nonce, buffer, tag.len())?;
+
+    let mut initial_counter_block = counter_block(nonce);
+    let s_0 = key.encrypt_stream(initial_counter_block);
+    initial_counter_block[15] = 1;
+    ctr_in_place(key, initial_counter_block, counter_bits(nonce), buffer)?;
+
+    let mac = cbc_mac(key, nonce, aad, buffer, tag.len());
+    let mut expected_tag: Stream = [0; 16];
+    let expected_tag = &mut expected_tag[..tag.len()];
+    for (i, byte) in expected_tag.iter_mut().enumerate() {
+        *byte = mac[i] ^ s_0[i];
+    }
+    if ct_eq(expected_tag, tag) {
+        Ok(())
+    } else {
+        ctr_in_place(key, initial_counter_block, counter_bits(nonce), buffer)?;
+        Err(Error::AuthenticationFailed)
+    }
+}
+
+fn check_parameters(nonce: &[u8], message: &[u8], tag_len: usize) -> Result<(), Error> {
+    if !(7..=13).contains(&nonce.len()) {
+        return Err(Error::InvalidNonceLength);
+    }
+    if !(4..=16).contains(&tag_len) || tag_len % 2 != 0 {
+        return Err(Error::InvalidTagLength);
+    }
+    // The length of the message has to fit in the 15 - nonce length bytes of B_0
+    let length_bits = counter_bits(nonce);
+    if length_bits < 64 && message.len() as u64 >= 1 << length_bits {
+        return Err(Error::InvalidLength);
+    }
+    Ok(())
+}
+
+// The counter, and the message length in B_0, take the bytes after the flags and nonce
+fn counter_bits(nonce: &[u8]) -> u32 {
+    (15 - nonce.len() as u32) * 8
+}
+
+// Ctr_0 = flags || nonce || 0, where the flags only encode the size of the counter
+fn counter_block(nonce: &[u8]) -> Stream {
+    let mut block: Stream = [0; 16];
+    block[0] = (14 - nonce.len()) as u8;
+    block[1..1 + nonce.len()].copy_from_slice(nonce);
+    block
+}
+
+// CBC-MAC over B_0, the encoded AAD and the plaintext, each zero padded to full blocks. The
+// blocks are chained as they are read, so the message is never copied.
+fn cbc_mac<K: AesKey>(
+    key: K,
+    nonce: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+    tag_len: usize,
+) -> Stream {
+    // B_0 = flags || nonce || plaintext length, the flags encode whether there is AAD, the
+    // tag length and the size of the length field
+    let mut b_0: Stream = [0; 16];
+    b_0[0] = (((tag_len - 2) / 2) << 3) as u8 | (14 - nonce.len()) as u8;
+    if !aad.is_empty() {
+        b_0[0] |= 0x40;
+    }
+    b_0[1..1 + nonce.len()].copy_from_slice(nonce);
+    let plaintext_len = (plaintext.len() as u64).to_be_bytes();
+    let length_bytes = 15 - nonce.len();
+    for i in 0..length_bytes.min(8) {
+        b_0[15 - i] = plaintext_len[7 - i];
+    }
+    let mut mac = key.encrypt_stream(b_0);
+
+    if !aad.is_empty() {
+        // The AAD is prefixed with its length, encoded in 2, 6 or 10 bytes, which shares the
+        // first block with the start of the AAD
+        let aad_len = aad.len() as u64;
+        let mut block: Stream = [0; 16];
+        let prefix_len = if aad_len < 0xFF00 {
+            block[..2].copy_from_slice(&(aad_len as u16).to_be_bytes());
+            2
+        } else if aad_len <= u32::MAX as u64 {
+            block[..2].copy_from_slice(&[0xFF, 0xFE]);
+            block[2..6].copy_from_slice(&(aad_len as u32).to_be_bytes());
+            6
+        } else {
+            block[..2].copy_from_slice(&[0xFF, 0xFF]);
+            block[2..10].copy_from_slice(&aad_len.to_be_bytes());
+            10
+        };
+        let (head, tail) = aad.split_at(aad.len().min(16 - prefix_len));
+        block[prefix_len..prefix_len + head.len()].copy_from_slice(head);
+        mac = key.encrypt_stream(stream_xor(mac, block));
+        mac = cbc_mac_padded(key, mac, tail);
+    }
+    cbc_mac_padded(key, mac, plaintext)
+}
+
+// CBC-MAC over bytes split into zero padded blocks, continuing from `mac`
+fn cbc_mac_padded<K: AesKey>(key: K, mut mac: Stream, bytes: &[u8]) -> Stream {
+    for chunk in bytes.chunks(16) {
+        let mut block: Stream = [0; 16];
+        block[..chunk.len()].copy_from_slice(chunk);
+        mac = key.encrypt_stream(stream_xor(mac, block));
+    }
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ccm_rfc_3610() {
+        for vector in RFC_3610 {
+            let [key, nonce, aad, plaintext, expected] = vector.map(hex_to_bytes);
+            let key: [u8; 16] = key.try_into().unwrap();
+            let (expected_ciphertext, expected_tag) = expected.split_at(plaintext.len());
+
+            let (ciphertext, tag) =
+                ccm_encrypt(key, &nonce, &aad, &plaintext, expected_tag.len()).unwrap();
+            assert_eq!(ciphertext, expected_ciphertext);
+            assert_eq!(tag, expected_tag);
+            assert_eq!(
+                ccm_decrypt(key, &nonce, &aad, &ciphertext, &tag),
+                Ok(plaintext)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ccm_in_place() {
+        for vector in RFC_3610 {
+            let [key, nonce, aad, plaintext, expected] = vector.map(hex_to_bytes);
+            let key: [u8; 16] = key.try_into().unwrap();
+            let (expected_ciphertext, expected_tag) = expected.split_at(plaintext.len());
+
+            let mut buffer = plaintext.clone();
+            let mut tag = [0; 16];
+            let tag = &mut tag[..expected_tag.len()];
+            assert_eq!(
+                ccm_encrypt_in_place(key, &nonce, &aad, &mut buffer, tag),
+                Ok(())
+            );
+            assert_eq!(buffer, expected_ciphertext);
+            assert_eq!(tag, expected_tag);
+            assert_eq!(
+                ccm_decrypt_in_place(key, &nonce, &aad, &mut buffer, tag),
+                Ok(())
+            );
+            assert_eq!(buffer, plaintext);
+
+            // A forged tag leaves the ciphertext in the buffer, not the plaintext
+            let mut buffer = expected_ciphertext.to_vec();
+            tag[0] ^= 1;
+            assert_eq!(
+                ccm_decrypt_in_place(key, &nonce, &aad, &mut buffer, tag),
+                Err(Error::AuthenticationFailed)
+            );
+            assert_eq!(buffer, expected_ciphertext);
+        }
+
+        let mut buffer = [0xAA; 20];
+        assert_eq!(
+            ccm_encrypt_in_place([0u8; 16], &[0; 6], &[], &mut buffer, &mut [0; 16]),
+            Err(Error::InvalidNonceLength)
+        );
+        assert_eq!(
+            ccm_decrypt_in_place([0u8; 16], &[0; 12], &[], &mut buffer, &[0; 5]),
+            Err(Error::InvalidTagLength)
+        );
+        assert_eq!(buffer, [0xAA; 20]);
+    }
+
+    // SP 800-38C, Appendix C, examples 1 to 4
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ccm_sp_800_38c() {
+        let key: [u8; 16] = hex_to_bytes("404142434445464748494a4b4c4d4e4f")
+            .try_into()
+            .unwrap();
+        let examples = [
+            (
+                "10111213141516",
+                hex_to_bytes("0001020304050607"),
+                "20212223",
+                "7162015b",
+                "4dac255d",
+            ),
+            (
+                "1011121314151617",
+                hex_to_bytes("000102030405060708090a0b0c0d0e0f"),
+                "202122232425262728292a2b2c2d2e2f",
+                "d2a1f0e051ea5f62081a7792073d593d",
+                "1fc64fbfaccd",
+            ),
+            (
+                "101112131415161718191a1b",
+                hex_to_bytes("000102030405060708090a0b0c0d0e0f10111213"),
+                "202122232425262728292a2b2c2d2e2f3031323334353637",
+                "e3b201a9f5b71a7a9b1ceaeccd97e70b6176aad9a4428aa5",
+                "484392fbc1b09951",
+            ),
+            (
+                "101112131415161718191a1b1c",
+                // 2^16 bytes of AAD, which need the 6 byte length encoding
+                (0..65536).map(|i| i as u8).collect(),
+                "202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f",
+                "69915dad1e84c6376a68c2967e4dab615ae0fd1faec44cc484828529463ccf72",
+                "b4ac6bec93e8598e7f0dadbcea5b",
+            ),
+        ];
+        for (nonce, aad, plaintext, expected_ciphertext, expected_tag) in examples {
+            let nonce = hex_to_bytes(nonce);
+            let plaintext = hex_to_bytes(plaintext);
+            let expected_tag = hex_to_bytes(expected_tag);
+
+            let (ciphertext, tag) =
+                ccm_encrypt(key, &nonce, &aad, &plaintext, expected_tag.len()).unwrap();
+            assert_eq!(ciphertext, hex_to_bytes(expected_ciphertext));
+            assert_eq!(tag, expected_tag);
+            assert_eq!(
+                ccm_decrypt(key, &nonce, &aad, &ciphertext, &tag),
+                Ok(plaintext)
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ccm_rejects_forgeries() {
+        let key = [0x42u8; 24];
+        let nonce = [0x24; 11];
+        let (ciphertext, tag) = ccm_encrypt(key, &nonce, b"header", b"sensor reading", 12).unwrap();
+
+        let mut forged_ciphertext = ciphertext.clone();
+        forged_ciphertext[0] ^= 1;
+        assert_eq!(
+            ccm_decrypt(key, &nonce, b"header", &forged_ciphertext, &tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            ccm_decrypt(key, &nonce, b"Header", &ciphertext, &tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            ccm_decrypt(key, &nonce, b"header", &ciphertext, &tag[..10]),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ccm_parameters() {
+        let key = [0u8; 32];
+        assert_eq!(
+            ccm_encrypt(key, &[0; 6], &[], &[], 16),
+            Err(Error::InvalidNonceLength)
+        );
+        assert_eq!(
+            ccm_encrypt(key, &[0; 14], &[], &[], 16),
+            Err(Error::InvalidNonceLength)
+        );
+        assert_eq!(
+            ccm_encrypt(key, &[0; 12], &[], &[], 5),
+            Err(Error::InvalidTagLength)
+        );
+        assert_eq!(
+            ccm_encrypt(key, &[0; 12], &[], &[], 18),
+            Err(Error::InvalidTagLength)
+        );
+        // A 13 byte nonce leaves 2 bytes for the plaintext length
+        assert_eq!(
+            ccm_encrypt(key, &[0; 13], &[], &[0; 65536], 16),
+            Err(Error::InvalidLength)
+        );
+        assert!(ccm_encrypt(key, &[0; 12], &[], &[0; 65536], 16).is_ok());
+    }
+
+    // RFC 3610, packet vectors #1 to #24: key, nonce, AAD, plaintext and ciphertext followed
+    // by the tag
+    const RFC_3610: [[&str; 5]; 24] = [
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "00000003020100a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+            "588c979a61c663d2f066d0c2c0f989806d5f6b61dac38417e8d12cfdf926e0",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "00000004030201a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "72c91a36e135f8cf291ca894085c87e3cc15c439c9e43a3ba091d56e10400916",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "00000005040302a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+            "51b1e5f44a197d1da46b0f8e2d282ae871e838bb64da8596574adaa76fbd9fb0\
+             c5",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "00000006050403a0a1a2a3a4a5",
+            "000102030405060708090a0b",
+            "0c0d0e0f101112131415161718191a1b1c1d1e",
+            "a28c6865939a9a79faaa5c4c2a9d4a91cdac8c96c861b9c9e61ef1",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "00000007060504a0a1a2a3a4a5",
+            "000102030405060708090a0b",
+            "0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "dcf1fb7b5d9e23fb9d4e131253658ad86ebdca3e51e83f077d9c2d93",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "00000008070605a0a1a2a3a4a5",
+            "000102030405060708090a0b",
+            "0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+            "6fc1b011f006568b5171a42d953d469b2570a4bd87405a0443ac91cb94",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "00412b4ea9cdbe3c9696766cfa",
+            "0be1a88bace018b1",
+            "08e8cf97d820ea258460e96ad9cf5289054d895ceac47c",
+            "4cb97f86a2a4689a877947ab8091ef5386a6ffbdd080f8e78cf7cb0cddd7b3",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "0033568ef7b2633c9696766cfa",
+            "63018f76dc8a1bcb",
+            "9020ea6f91bdd85afa0039ba4baff9bfb79c7028949cd0ec",
+            "4ccb1e7ca981befaa0726c55d378061298c85c92814abc33c52ee81d7d77c08a",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "00103fe41336713c9696766cfa",
+            "aa6cfa36cae86b40",
+            "b916e0eacc1c00d7dcec68ec0b3bbb1a02de8a2d1aa346132e",
+            "b1d23a2220ddc0ac900d9aa03c61fcf4a559a4417767089708a776796edb7235\
+             06",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "00764c63b8058e3c9696766cfa",
+            "d0d0735c531e1becf049c244",
+            "12daac5630efa5396f770ce1a66b21f7b2101c",
+            "14d253c3967b70609b7cbb7c499160283245269a6f49975bcadeaf",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "00f8b678094e3b3c9696766cfa",
+            "77b60f011c03e1525899bcae",
+            "e88b6a46c78d63e52eb8c546efb5de6f75e9cc0d",
+            "5545ff1a085ee2efbf52b2e04bee1e2336c73e3f762c0c7744fe7e3c",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "00d560912d3f703c9696766cfa",
+            "cd9044d2b71fdb8120ea60c0",
+            "6435acbafb11a82e2f071d7ca4a5ebd93a803ba87f",
+            "009769ecabdf48625594c59251e6035722675e04c847099e5ae0704551",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "00000009080706a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+            "0135d1b2c95f41d5d1d4fec185d166b8094e999dfed96c048c56602c97acbb74\
+             90",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "0000000a090807a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "7b75399ac0831dd2f0bbd75879a2fd8f6cae6b6cd9b7db24c17b4433f434963f\
+             34b4",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "0000000b0a0908a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+            "82531a60cc24945a4b8279181ab5c84df21ce7f9b73f42e197ea9c07e56b5eb1\
+             7e5f4e",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "0000000c0b0a09a0a1a2a3a4a5",
+            "000102030405060708090a0b",
+            "0c0d0e0f101112131415161718191a1b1c1d1e",
+            "07342594157785152b074098330abb141b947b566aa9406b4d999988dd",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "0000000d0c0b0aa0a1a2a3a4a5",
+            "000102030405060708090a0b",
+            "0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "676bb20380b0e301e8ab79590a396da78b834934f53aa2e9107a8b6c022c",
+        ],
+        [
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "0000000e0d0c0ba0a1a2a3a4a5",
+            "000102030405060708090a0b",
+            "0c0d0e0f101112131415161718191a1b1c1d1e1f20",
+            "c0ffa0d6f05bdb67f24d43a4338d2aa4bed7b20e43cd1aa31662e7ad65d6db",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "0042fff8f1951c3c9696766cfa",
+            "d85bc7e69f944fb8",
+            "8a19b950bcf71a018e5e6701c91787659809d67dbedd18",
+            "bc218daa947427b6db386a99ac1aef23ade0b52939cb6a637cf9bec2408897c6\
+             ba",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "00920f40e56cdc3c9696766cfa",
+            "74a0ebc9069f5b37",
+            "1761433c37c5a35fc1f39f406302eb907c6163be38c98437",
+            "5810e6fd25874022e80361a478e3e9cf484ab04f447efff6f0a477cc2fc9bf54\
+             8944",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "0027ca0c7120bc3c9696766cfa",
+            "44a3aa3aae6475ca",
+            "a434a8e58500c6e41530538862d686ea9e81301b5ae4226bfa",
+            "f2beed7bc5098e83feb5b31608f8e29c38819a89c8e776f1544d4151a4ed3a8b\
+             87b9ce",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "005b8ccbcd9af83c9696766cfa",
+            "ec46bb63b02520c33c49fd70",
+            "b96b49e21d621741632875db7f6c9243d2d7c2",
+            "31d750a09da3ed7fddd49a2032aabf17ec8ebf7d22c8088c666be5c197",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "003ebe94044b9a3c9696766cfa",
+            "47a65ac78b3d594227e85e71",
+            "e2fcfbb880442c731bf95167c8ffd7895e337076",
+            "e882f1dbd38ce3eda7c23f04dd65071eb41342acdf7e00dccec7ae52987d",
+        ],
+        [
+            "d7828d13b2b0bdc325a76236df93cc6b",
+            "008d493b30ae8b3c9696766cfa",
+            "6e37a6ef546d955d34ab6059",
+            "abf21c0b02feb88f856df4a37381bce3cc128517d4",
+            "f32905b88a641b04b9c9ffb58cc390900f3da12ab16dce9e82efa16da62059",
+        ],
+    ];
+}
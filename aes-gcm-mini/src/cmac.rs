@@ -0,0 +1,200 @@
+use crate::aes::AesKey;
+use crate::error::Error;
+use crate::utils::{bits_to_stream, ct_eq, stream_to_bits, stream_xor, Stream};
+
+// AES-CMAC (RFC 4493, SP 800-38B) of a message given at once
+pub fn cmac<K: AesKey>(key: K, message: &[u8]) -> Stream {
+    let mut mac = Cmac::new(key);
+    mac.update(message);
+    mac.finalize()
+}
+
+/// Streaming AES-CMAC, for messages that are not available at once.
+///
+/// The last block is masked with one of the subkeys depending on whether it is complete, so the
+/// most recent block is only processed once more data arrives or the MAC is finalized.
+#[derive(Clone)]
+pub struct Cmac<K: AesKey> {
+    key: K,
+    k1: Stream,
+    k2: Stream,
+    state: Stream,
+    buffer: Stream,
+    buffer_len: usize,
+}
+
+impl<K: AesKey> Cmac<K> {
+    pub fn new(key: K) -> Self {
+        let (k1, k2) = generate_subkeys(key);
+        Self {
+            key,
+            k1,
+            k2,
+            state: [0; 16],
+            buffer: [0; 16],
+            buffer_len: 0,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            if self.buffer_len == 16 {
+                self.state = encrypt(self.key, stream_xor(self.state, self.buffer));
+                self.buffer_len = 0;
+            }
+            self.buffer[self.buffer_len] = byte;
+            self.buffer_len += 1;
+        }
+    }
+
+    pub fn finalize(self) -> Stream {
+        // A complete last block is masked with K1, an incomplete one is padded with 10^i and
+        // masked with K2
+        let last_block = if self.buffer_len == 16 {
+            stream_xor(self.buffer, self.k1)
+        } else {
+            let mut padded: Stream = [0; 16];
+            padded[..self.buffer_len].copy_from_slice(&self.buffer[..self.buffer_len]);
+            padded[self.buffer_len] = 0x80;
+            stream_xor(padded, self.k2)
+        };
+        encrypt(self.key, stream_xor(self.state, last_block))
+    }
+
+    /// Checks the MAC of the data so far against a tag, in constant time.
+    pub fn verify(self, tag: &Stream) -> Result<(), Error> {
+        if ct_eq(&self.finalize(), tag) {
+            Ok(())
+        } else {
+            Err(Error::AuthenticationFailed)
+        }
+    }
+}
+
+fn encrypt<K: AesKey>(key: K, stream: Stream) -> Stream {
+    key.encrypt_stream(stream)
+}
+
+// K1 and K2 are the doublings of the encrypted zero block
+fn generate_subkeys<K: AesKey>(key: K) -> (Stream, Stream) {
+    let l = encrypt(key, [0; 16]);
+    let k1 = dbl(l);
+    let k2 = dbl(k1);
+    (k1, k2)
+}
+
+/// Multiplication of the binary extension field by x, with the CMAC bit order
+/// left shifts a block by one bit
+/// if the msb is one, then the last byte is XORed with 0x87
+/// this 0x87 is the hex representation of 10000111, which represents
+/// the polynomial x^7 + x^2 + x + 1
+pub(crate) fn dbl(stream: Stream) -> Stream {
+    let bits = stream_to_bits(stream);
+    let mut result = [0; 128];
+    result[..127].copy_from_slice(&bits[1..]);
+    let mut result_stream = bits_to_stream(result);
+    if bits[0] == 1 {
+        result_stream[15] ^= 0x87;
+    }
+    result_stream
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    // RFC 4493, section 4
+    #[test]
+    fn test_generate_subkeys() {
+        let key: [u8; 16] = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+            .try_into()
+            .unwrap();
+        let (k1, k2) = generate_subkeys(key);
+        assert_eq!(
+            k1.to_vec(),
+            hex_to_bytes("fbeed618357133667c85e08f7236a8de")
+        );
+        assert_eq!(
+            k2.to_vec(),
+            hex_to_bytes("f7ddac306ae266ccf90bc11ee46d513b")
+        );
+    }
+
+    fn check_examples<K: AesKey>(key: K, expected_macs: [&str; 4]) {
+        let message = hex_to_bytes(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        );
+        for (len, expected_mac) in [0, 16, 40, 64].into_iter().zip(expected_macs) {
+            let expected_mac: Stream = hex_to_bytes(expected_mac).try_into().unwrap();
+            assert_eq!(cmac(key, &message[..len]), expected_mac);
+
+            // Streaming in uneven chunks gives the same MAC
+            let mut mac = Cmac::new(key);
+            for chunk in message[..len].chunks(7) {
+                mac.update(chunk);
+            }
+            assert_eq!(mac.clone().finalize(), expected_mac);
+            assert_eq!(mac.verify(&expected_mac), Ok(()));
+        }
+    }
+
+    // RFC 4493, section 4, examples 1 to 4
+    #[test]
+    fn test_cmac_rfc_4493() {
+        let key: [u8; 16] = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+            .try_into()
+            .unwrap();
+        check_examples(
+            key,
+            [
+                "bb1d6929e95937287fa37d129b756746",
+                "070a16b46b4d4144f79bdd9dd04a287c",
+                "dfa66747de9ae63030ca32611497c827",
+                "51f0bebf7e3b9d92fc49741779363cfe",
+            ],
+        );
+    }
+
+    // SP 800-38B, appendix D.2 and D.3
+    #[test]
+    fn test_cmac_sp_800_38b() {
+        let key: [u8; 24] = hex_to_bytes("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b")
+            .try_into()
+            .unwrap();
+        check_examples(
+            key,
+            [
+                "d17ddf46adaacde531cac483de7a9367",
+                "9e99a7bf31e710900662f65e617c5184",
+                "8a1de5be2eb31aad089a82e6ee908b0e",
+                "a1d5df0eed790f794d77589659f39a11",
+            ],
+        );
+
+        let key: [u8; 32] =
+            hex_to_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .try_into()
+                .unwrap();
+        check_examples(
+            key,
+            [
+                "028962f61b7bf89efc6b551f4667d983",
+                "28a7023f452e8f82bd4bf28d8c37c35c",
+                "aaf3d8f1de5640c232f5b169b9c911e6",
+                "e1992190549f6ed5696a2c056c315410",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_cmac_verify_rejects() {
+        let key = [0x42u8; 16];
+        let tag = cmac(key, b"message");
+
+        let mut mac = Cmac::new(key);
+        mac.update(b"messagf");
+        assert_eq!(mac.verify(&tag), Err(Error::AuthenticationFailed));
+    }
+}
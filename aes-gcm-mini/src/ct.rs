@@ -0,0 +1,413 @@
+//! Constant-time replacements for the S-box lookups, `xtime` and the GHASH multiplication,
+//! enabled with the `constant-time` feature.
+//!
+//! The table S-box indexes memory with secret bytes, and `gfmulx`, `gmul` and `mulx` branch on
+//! bits of secret values, which leaks through the cache and the branch predictor on native
+//! targets. Here the S-box is evaluated as a boolean circuit over all bytes of the state at
+//! once, bitsliced with one bit of every byte per word, the inverse S-box is computed
+//! arithmetically, and the conditional reductions and additions are masked with `0 - bit`.
+//!
+//! The `dudect` tests measure whether the running time depends on the secret input, they are
+//! ignored by default and meant to be run natively in release mode:
+//! `cargo test -p aes-gcm-mini --release --features constant-time -- --ignored dudect`
+//! They measure the functions the feature flags dispatch to, so they fail without
+//! `constant-time`, and `dudect_bitwise_gmul` checks that the harness does detect the
+//! branching GHASH multiplication.
+
+use crate::utils::{wipe, State, Stream};
+
+// All ones if `bit` is 1 and zero if it is 0. Without the `black_box` LLVM recognizes
+// `value & mask` as a select and compiles the GHASH multiplication back into a branch, which
+// the `dudect_gmul` test picks up on x86-64.
+fn mask(bit: u8) -> u8 {
+    core::hint::black_box(bit.wrapping_neg())
+}
+
+// Branch-free multiplication by x modulo x^8 + x^4 + x^3 + x + 1
+pub(crate) fn xtime(byte: u8) -> u8 {
+    (byte << 1) ^ (0x1b & mask(byte >> 7))
+}
+
+// GHASH multiplication, adding `accumulator` for every set bit of `x` through a mask
+pub(crate) fn gmul(x: Stream, y: Stream) -> Stream {
+    let mut result: Stream = [0; 16];
+    let mut accumulator = y;
+    for i in 0..128 {
+        let mask = mask((x[i / 8] >> (7 - i % 8)) & 1);
+        for j in 0..16 {
+            result[j] ^= accumulator[j] & mask;
+        }
+        accumulator = mulx(accumulator);
+    }
+    wipe(&mut accumulator);
+    result
+}
+
+// Multiplication by x in the bit reflected GHASH field, reducing with a mask of the dropped bit
+pub(crate) fn mulx(block: Stream) -> Stream {
+    let mut result: Stream = [0; 16];
+    let mut carry = 0;
+    for i in 0..16 {
+        result[i] = (block[i] >> 1) | carry;
+        carry = block[i] << 7;
+    }
+    result[0] ^= 0xe1 & mask(block[15] & 1);
+    result
+}
+
+// SubBytes on the whole state with a single evaluation of the bitsliced S-box
+pub(crate) fn sub_bytes(state: State) -> State {
+    let mut bytes = [0; 16];
+    for i in 0..4 {
+        bytes[i * 4..i * 4 + 4].copy_from_slice(&state[i].to_be_bytes());
+    }
+    let bytes = unbitslice(bitsliced_sbox(bitslice(bytes)));
+    let mut new_state = state;
+    for i in 0..4 {
+        new_state[i] = u32::from_be_bytes([
+            bytes[i * 4],
+            bytes[i * 4 + 1],
+            bytes[i * 4 + 2],
+            bytes[i * 4 + 3],
+        ]);
+    }
+    new_state
+}
+
+// SubWord of the key expansion, the unused lanes are left at zero
+pub(crate) fn sub_word(word: u32) -> u32 {
+    let mut bytes = [0; 16];
+    bytes[..4].copy_from_slice(&word.to_be_bytes());
+    let bytes = unbitslice(bitsliced_sbox(bitslice(bytes)));
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// InvSubBytes on the whole state
+pub(crate) fn inv_sub_bytes(state: State) -> State {
+    let mut new_state = state;
+    for i in 0..4 {
+        new_state[i] = u32::from_be_bytes(state[i].to_be_bytes().map(inv_sbox));
+    }
+    new_state
+}
+
+// The inverse S-box undoes the affine transformation and then inverts in GF(2^8)
+pub(crate) fn inv_sbox(byte: u8) -> u8 {
+    let affine_inverse = byte.rotate_left(1) ^ byte.rotate_left(3) ^ byte.rotate_left(6) ^ 0x05;
+    gf_inverse(affine_inverse)
+}
+
+// Inversion as x^254, with 0 mapped to 0, through a fixed chain of squarings and
+// multiplications
+fn gf_inverse(byte: u8) -> u8 {
+    let x2 = gf_mul(byte, byte);
+    let x3 = gf_mul(x2, byte);
+    let x6 = gf_mul(x3, x3);
+    let x12 = gf_mul(x6, x6);
+    let x15 = gf_mul(x12, x3);
+    let x30 = gf_mul(x15, x15);
+    let x60 = gf_mul(x30, x30);
+    let x120 = gf_mul(x60, x60);
+    let x126 = gf_mul(x120, x6);
+    let x127 = gf_mul(x126, byte);
+    gf_mul(x127, x127)
+}
+
+// Multiplication in GF(2^8), masking instead of branching on the bits of `b`
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut result = 0;
+    let mut accumulator = a;
+    for i in 0..8 {
+        result ^= accumulator & mask((b >> i) & 1);
+        accumulator = xtime(accumulator);
+    }
+    result
+}
+
+// Word `b` holds bit `b` of every byte, byte `k` in bit `k` of each word
+fn bitslice(bytes: [u8; 16]) -> [u16; 8] {
+    let mut slices = [0; 8];
+    for (b, slice) in slices.iter_mut().enumerate() {
+        for (k, byte) in bytes.iter().enumerate() {
+            *slice |= (((byte >> b) & 1) as u16) << k;
+        }
+    }
+    slices
+}
+
+fn unbitslice(slices: [u16; 8]) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    for (k, byte) in bytes.iter_mut().enumerate() {
+        for (b, slice) in slices.iter().enumerate() {
+            *byte |= (((slice >> k) & 1) as u8) << b;
+        }
+    }
+    bytes
+}
+
+// The 113 gate S-box circuit of Boyar and Peralta, "A depth-16 circuit for the AES S-box"
+// (2011), with x0 and s0 the most significant bits of the input and output bytes. The top
+// and bottom linear layers map into and out of the tower field GF(((2^2)^2)^2), where the
+// non-linear middle section computes the inverse.
+fn bitsliced_sbox(slices: [u16; 8]) -> [u16; 8] {
+    let x0 = slices[7];
+    let x1 = slices[6];
+    let x2 = slices[5];
+    let x3 = slices[4];
+    let x4 = slices[3];
+    let x5 = slices[2];
+    let x6 = slices[1];
+    let x7 = slices[0];
+
+    // Top linear transformation
+    let y14 = x3 ^ x5;
+    let y13 = x0 ^ x6;
+    let y9 = x0 ^ x3;
+    let y8 = x0 ^ x5;
+    let t0 = x1 ^ x2;
+    let y1 = t0 ^ x7;
+    let y4 = y1 ^ x3;
+    let y12 = y13 ^ y14;
+    let y2 = y1 ^ x0;
+    let y5 = y1 ^ x6;
+    let y3 = y5 ^ y8;
+    let t1 = x4 ^ y12;
+    let y15 = t1 ^ x5;
+    let y20 = t1 ^ x1;
+    let y6 = y15 ^ x7;
+    let y10 = y15 ^ t0;
+    let y11 = y20 ^ y9;
+    let y7 = x7 ^ y11;
+    let y17 = y10 ^ y11;
+    let y19 = y10 ^ y8;
+    let y16 = t0 ^ y11;
+    let y21 = y13 ^ y16;
+    let y18 = x0 ^ y16;
+
+    // Non-linear section
+    let t2 = y12 & y15;
+    let t3 = y3 & y6;
+    let t4 = t3 ^ t2;
+    let t5 = y4 & x7;
+    let t6 = t5 ^ t2;
+    let t7 = y13 & y16;
+    let t8 = y5 & y1;
+    let t9 = t8 ^ t7;
+    let t10 = y2 & y7;
+    let t11 = t10 ^ t7;
+    let t12 = y9 & y11;
+    let t13 = y14 & y17;
+    let t14 = t13 ^ t12;
+    let t15 = y8 & y10;
+    let t16 = t15 ^ t12;
+    let t17 = t4 ^ t14;
+    let t18 = t6 ^ t16;
+    let t19 = t9 ^ t14;
+    let t20 = t11 ^ t16;
+    let t21 = t17 ^ y20;
+    let t22 = t18 ^ y19;
+    let t23 = t19 ^ y21;
+    let t24 = t20 ^ y18;
+
+    let t25 = t21 ^ t22;
+    let t26 = t21 & t23;
+    let t27 = t24 ^ t26;
+    let t28 = t25 & t27;
+    let t29 = t28 ^ t22;
+    let t30 = t23 ^ t24;
+    let t31 = t22 ^ t26;
+    let t32 = t31 & t30;
+    let t33 = t32 ^ t24;
+    let t34 = t23 ^ t33;
+    let t35 = t27 ^ t33;
+    let t36 = t24 & t35;
+    let t37 = t36 ^ t34;
+    let t38 = t27 ^ t36;
+    let t39 = t29 & t38;
+    let t40 = t25 ^ t39;
+
+    let t41 = t40 ^ t37;
+    let t42 = t29 ^ t33;
+    let t43 = t29 ^ t40;
+    let t44 = t33 ^ t37;
+    let t45 = t42 ^ t41;
+    let z0 = t44 & y15;
+    let z1 = t37 & y6;
+    let z2 = t33 & x7;
+    let z3 = t43 & y16;
+    let z4 = t40 & y1;
+    let z5 = t29 & y7;
+    let z6 = t42 & y11;
+    let z7 = t45 & y17;
+    let z8 = t41 & y10;
+    let z9 = t44 & y12;
+    let z10 = t37 & y3;
+    let z11 = t33 & y4;
+    let z12 = t43 & y13;
+    let z13 = t40 & y5;
+    let z14 = t29 & y2;
+    let z15 = t42 & y9;
+    let z16 = t45 & y14;
+    let z17 = t41 & y8;
+
+    // Bottom linear transformation
+    let t46 = z15 ^ z16;
+    let t47 = z10 ^ z11;
+    let t48 = z5 ^ z13;
+    let t49 = z9 ^ z10;
+    let t50 = z2 ^ z12;
+    let t51 = z2 ^ z5;
+    let t52 = z7 ^ z8;
+    let t53 = z0 ^ z3;
+    let t54 = z6 ^ z7;
+    let t55 = z16 ^ z17;
+    let t56 = z12 ^ t48;
+    let t57 = t50 ^ t53;
+    let t58 = z4 ^ t46;
+    let t59 = z3 ^ t54;
+    let t60 = t46 ^ t57;
+    let t61 = z14 ^ t57;
+    let t62 = t52 ^ t58;
+    let t63 = t49 ^ t58;
+    let t64 = z4 ^ t59;
+    let t65 = t61 ^ t62;
+    let t66 = z1 ^ t63;
+    let s0 = t59 ^ t63;
+    let s6 = t56 ^ !t62;
+    let s7 = t48 ^ !t60;
+    let t67 = t64 ^ t65;
+    let s3 = t53 ^ t66;
+    let s4 = t51 ^ t66;
+    let s5 = t47 ^ t65;
+    let s1 = t64 ^ !s3;
+    let s2 = t55 ^ !t67;
+
+    [s7, s6, s5, s4, s3, s2, s1, s0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::AesKey;
+    use crate::ghash::{self, bitwise_gmul, bitwise_mulx};
+    use crate::utils::{state_to_stream, stream_to_state, Rng};
+    use std::hint::black_box;
+    use std::time::Instant;
+
+    #[test]
+    fn test_gmul() {
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        for _ in 0..1000 {
+            let (x, y) = (rng.stream(), rng.stream());
+            assert_eq!(gmul(x, y), bitwise_gmul(x, y));
+            assert_eq!(gmul(x, y), gmul(y, x));
+        }
+
+        let mut one: Stream = [0; 16];
+        one[0] = 0x80;
+        let x = rng.stream();
+        assert_eq!(gmul(one, x), x);
+        assert_eq!(gmul([0; 16], x), [0; 16]);
+    }
+
+    #[test]
+    fn test_mulx() {
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        let mut x: Stream = [0; 16];
+        x[0] = 0x40;
+        for _ in 0..1000 {
+            let y = rng.stream();
+            assert_eq!(mulx(y), bitwise_mulx(y));
+            // x is the element 0x40 followed by zeros
+            assert_eq!(mulx(y), gmul(x, y));
+        }
+    }
+
+    // Welch's t statistic between the timings of a fixed input class and a random input class,
+    // as in "Dude, is my code constant time?" (Reparaz, Balasch, Verbauwhede, 2017). Classes
+    // are interleaved at random, all inputs are generated up front and the slowest measurements
+    // are cropped to reduce noise.
+    fn dudect(measurements: usize, fixed: Stream, mut f: impl FnMut(Stream) -> Stream) -> f64 {
+        let mut rng = Rng(0xdeadbeefcafef00d);
+        let inputs: Vec<(usize, Stream)> = (0..measurements)
+            .map(|_| match rng.next_u64() & 1 {
+                0 => (0, fixed),
+                _ => (1, rng.stream()),
+            })
+            .collect();
+
+        let mut samples: [Vec<f64>; 2] = [Vec::new(), Vec::new()];
+        for (class, input) in inputs {
+            let start = Instant::now();
+            black_box(f(black_box(input)));
+            samples[class].push(start.elapsed().as_nanos() as f64);
+        }
+
+        let mut all: Vec<f64> = samples.iter().flatten().copied().collect();
+        all.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let cutoff = all[all.len() * 9 / 10];
+
+        let [mean0, mean1] = samples.clone().map(|s| mean(&s, cutoff).0);
+        let [var0, var1] = samples.clone().map(|s| mean(&s, cutoff).1);
+        let [n0, n1] = samples.map(|s| s.iter().filter(|&&t| t <= cutoff).count() as f64);
+        (mean0 - mean1) / (var0 / n0 + var1 / n1).sqrt()
+    }
+
+    // Mean and variance of the samples up to `cutoff`
+    fn mean(samples: &[f64], cutoff: f64) -> (f64, f64) {
+        let kept: Vec<f64> = samples.iter().copied().filter(|&t| t <= cutoff).collect();
+        let mean = kept.iter().sum::<f64>() / kept.len() as f64;
+        let variance = kept.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / kept.len() as f64;
+        (mean, variance)
+    }
+
+    // dudect treats |t| above 4.5 as evidence of a timing leak
+    const THRESHOLD: f64 = 4.5;
+
+    #[test]
+    #[ignore]
+    fn dudect_gmul() {
+        let y = Rng(1).stream();
+        let t = dudect(200_000, [0; 16], |x| ghash::gmul(x, y));
+        assert!(
+            t.abs() < THRESHOLD,
+            "gmul timing depends on its input, t = {t}"
+        );
+    }
+
+    // Negative control, the harness has to flag the multiplication that branches on the bits
+    // of its input
+    #[test]
+    #[ignore]
+    fn dudect_bitwise_gmul() {
+        let y = Rng(1).stream();
+        let t = dudect(200_000, [0; 16], |x| bitwise_gmul(x, y));
+        assert!(
+            t.abs() >= THRESHOLD,
+            "the branching gmul went undetected, t = {t}"
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn dudect_sub_bytes() {
+        let t = dudect(200_000, [0; 16], |x| {
+            state_to_stream(sub_bytes(stream_to_state(x)))
+        });
+        assert!(
+            t.abs() < THRESHOLD,
+            "sub_bytes timing depends on its input, t = {t}"
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn dudect_aes() {
+        let block = Rng(2).stream();
+        let t = dudect(200_000, [0; 16], |key| key.encrypt_stream(block));
+        assert!(
+            t.abs() < THRESHOLD,
+            "aes timing depends on the key, t = {t}"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use crate::aes::AesKey;
+use crate::error::Error;
+use crate::utils::Stream;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+// CTR mode encryption and decryption. The rightmost `counter_bits` bits of the counter block
+// are incremented modulo 2^counter_bits for every block, the remaining bits are left as is.
+// A trailing partial block uses the leftmost bytes of its encrypted counter block.
+#[cfg(feature = "alloc")]
+pub fn ctr<K: AesKey>(
+    key: K,
+    initial_counter_block: Stream,
+    counter_bits: u32,
+    input: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut output = input.to_vec();
+    ctr_in_place(key, initial_counter_block, counter_bits, &mut output)?;
+    Ok(output)
+}
+
+// CTR mode over `buffer` in place, the buffer is left untouched if the counter width is not
+// between 1 and 128 bits or the counter would overflow
+pub fn ctr_in_place<K: AesKey>(
+    key: K,
+    initial_counter_block: Stream,
+    counter_bits: u32,
+    buffer: &mut [u8],
+) -> Result<(), Error> {
+    if !(1..=128).contains(&counter_bits) {
+        return Err(Error::InvalidCounterWidth);
+    }
+    // Counter blocks must be unique, so the counter may not wrap around
+    let blocks = buffer.len().div_ceil(16) as u128;
+    if counter_bits < 128 && blocks > 1 << counter_bits {
+        return Err(Error::CounterOverflow);
+    }
+
+    let mut counter_block = initial_counter_block;
+    for chunk in buffer.chunks_mut(16) {
+        let encrypted_counter_block = key.encrypt_stream(counter_block);
+        for i in 0..chunk.len() {
+            chunk[i] ^= encrypted_counter_block[i];
+        }
+        counter_block = increment(counter_block, counter_bits);
+    }
+    Ok(())
+}
+
+// Increments the rightmost `counter_bits` bits of a counter block, as a big endian integer
+fn increment(counter_block: Stream, counter_bits: u32) -> Stream {
+    let mut incremented = counter_block;
+    let mut remaining_bits = counter_bits;
+    for i in (0..16).rev() {
+        let mask: u8 = if remaining_bits >= 8 {
+            0xFF
+        } else {
+            (1 << remaining_bits) - 1
+        };
+        let counter = (incremented[i] & mask).wrapping_add(1) & mask;
+        incremented[i] = (incremented[i] & !mask) | counter;
+
+        // Stop when there is no carry or the counter bits are exhausted
+        if counter != 0 || remaining_bits <= 8 {
+            break;
+        }
+        remaining_bits -= 8;
+    }
+    incremented
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    // SP 800-38A, F.5
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ctr() {
+        let initial_counter_block: Stream = hex_to_bytes("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")
+            .try_into()
+            .unwrap();
+        let plaintext = hex_to_bytes(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        );
+
+        let key: [u8; 16] = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+            .try_into()
+            .unwrap();
+        let ciphertext = hex_to_bytes(
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+             5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee",
+        );
+        assert_eq!(
+            ctr(key, initial_counter_block, 128, &plaintext),
+            Ok(ciphertext.clone())
+        );
+        assert_eq!(
+            ctr(key, initial_counter_block, 128, &ciphertext),
+            Ok(plaintext.clone())
+        );
+
+        let key: [u8; 24] = hex_to_bytes("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b")
+            .try_into()
+            .unwrap();
+        let ciphertext = hex_to_bytes(
+            "1abc932417521ca24f2b0459fe7e6e0b090339ec0aa6faefd5ccc2c6f4ce8e94\
+             1e36b26bd1ebc670d1bd1d665620abf74f78a7f6d29809585a97daec58c6b050",
+        );
+        assert_eq!(
+            ctr(key, initial_counter_block, 128, &plaintext),
+            Ok(ciphertext.clone())
+        );
+        assert_eq!(
+            ctr(key, initial_counter_block, 128, &ciphertext),
+            Ok(plaintext.clone())
+        );
+
+        let key: [u8; 32] =
+            hex_to_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .try_into()
+                .unwrap();
+        let ciphertext = hex_to_bytes(
+            "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c5\
+             2b0930daa23de94ce87017ba2d84988ddfc9c58db67aada613c2dd08457941a6",
+        );
+        assert_eq!(
+            ctr(key, initial_counter_block, 128, &plaintext),
+            Ok(ciphertext.clone())
+        );
+        assert_eq!(
+            ctr(key, initial_counter_block, 128, &ciphertext[..40]),
+            Ok(plaintext[..40].to_vec())
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ctr_counter_overflow() {
+        let key = [0u8; 16];
+        let initial_counter_block = [0u8; 16];
+        assert!(ctr(key, initial_counter_block, 1, &[0; 32]).is_ok());
+        assert_eq!(
+            ctr(key, initial_counter_block, 1, &[0; 33]),
+            Err(Error::CounterOverflow)
+        );
+        assert!(ctr(key, initial_counter_block, 8, &[0; 256 * 16]).is_ok());
+    }
+
+    #[test]
+    fn test_ctr_in_place() {
+        let initial_counter_block: Stream = hex_to_bytes("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")
+            .try_into()
+            .unwrap();
+        let key: [u8; 16] = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+            .try_into()
+            .unwrap();
+        // A partial final block only uses part of its key stream
+        let mut buffer = hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c");
+        assert_eq!(
+            ctr_in_place(key, initial_counter_block, 128, &mut buffer),
+            Ok(())
+        );
+        assert_eq!(
+            buffer,
+            hex_to_bytes("874d6191b620e3261bef6864990db6ce9806f66b7970fdff")
+        );
+
+        let mut buffer = [0xAA; 33];
+        assert_eq!(
+            ctr_in_place(key, initial_counter_block, 1, &mut buffer),
+            Err(Error::CounterOverflow)
+        );
+        assert_eq!(buffer, [0xAA; 33]);
+
+        for counter_bits in [0, 129] {
+            assert_eq!(
+                ctr_in_place(key, initial_counter_block, counter_bits, &mut buffer),
+                Err(Error::InvalidCounterWidth)
+            );
+            assert_eq!(buffer, [0xAA; 33]);
+        }
+    }
+
+    #[test]
+    fn test_increment() {
+        let mut block = [0x00; 16];
+        block[15] = 0xFF;
+        block[14] = 0x01;
+        let mut expected = [0x00; 16];
+        expected[14] = 0x02;
+        assert_eq!(increment(block, 32), expected);
+
+        // Only the counter bits wrap around
+        let block = [0xFF; 16];
+        let mut expected = [0xFF; 16];
+        expected[15] = 0xF0;
+        assert_eq!(increment(block, 4), expected);
+
+        let mut expected = [0xFF; 16];
+        expected[14] = 0xFE;
+        expected[15] = 0x00;
+        assert_eq!(increment(block, 9), expected);
+
+        assert_eq!(increment(block, 128), [0x00; 16]);
+    }
+}
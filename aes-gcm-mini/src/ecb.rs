@@ -0,0 +1,123 @@
+use crate::aes::AesKey;
+use crate::error::Error;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+// ECB encryption, every block of the plaintext is encrypted independently
+#[cfg(feature = "alloc")]
+pub fn ecb_encrypt<K: AesKey>(key: K, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut ciphertext = plaintext.to_vec();
+    ecb_encrypt_in_place(key, &mut ciphertext)?;
+    Ok(ciphertext)
+}
+
+// ECB decryption, every block of the ciphertext is decrypted independently
+#[cfg(feature = "alloc")]
+pub fn ecb_decrypt<K: AesKey>(key: K, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut plaintext = ciphertext.to_vec();
+    ecb_decrypt_in_place(key, &mut plaintext)?;
+    Ok(plaintext)
+}
+
+// ECB encryption of `buffer` in place
+pub fn ecb_encrypt_in_place<K: AesKey>(key: K, buffer: &mut [u8]) -> Result<(), Error> {
+    if buffer.len() % 16 != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    for chunk in buffer.chunks_mut(16) {
+        let block = key.encrypt_stream(chunk.try_into().unwrap());
+        chunk.copy_from_slice(&block);
+    }
+    Ok(())
+}
+
+// ECB decryption of `buffer` in place
+pub fn ecb_decrypt_in_place<K: AesKey>(key: K, buffer: &mut [u8]) -> Result<(), Error> {
+    if buffer.len() % 16 != 0 {
+        return Err(Error::InvalidLength);
+    }
+
+    for chunk in buffer.chunks_mut(16) {
+        let block = key.decrypt_stream(chunk.try_into().unwrap());
+        chunk.copy_from_slice(&block);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    // SP 800-38A, F.1
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ecb() {
+        let plaintext = hex_to_bytes(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        );
+
+        let key: [u8; 16] = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+            .try_into()
+            .unwrap();
+        let ciphertext = hex_to_bytes(
+            "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf\
+             43b1cd7f598ece23881b00e3ed0306887b0c785e27e8ad3f8223207104725dd4",
+        );
+        assert_eq!(ecb_encrypt(key, &plaintext), Ok(ciphertext.clone()));
+        assert_eq!(ecb_decrypt(key, &ciphertext), Ok(plaintext.clone()));
+
+        let key: [u8; 24] = hex_to_bytes("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b")
+            .try_into()
+            .unwrap();
+        let ciphertext = hex_to_bytes(
+            "bd334f1d6e45f25ff712a214571fa5cc974104846d0ad3ad7734ecb3ecee4eef\
+             ef7afd2270e2e60adce0ba2face6444e9a4b41ba738d6c72fb16691603c18e0e",
+        );
+        assert_eq!(ecb_encrypt(key, &plaintext), Ok(ciphertext.clone()));
+        assert_eq!(ecb_decrypt(key, &ciphertext), Ok(plaintext.clone()));
+
+        let key: [u8; 32] =
+            hex_to_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .try_into()
+                .unwrap();
+        let ciphertext = hex_to_bytes(
+            "f3eed1bdb5d2a03c064b5a7e3db181f8591ccb10d410ed26dc5ba74a31362870\
+             b6ed21b99ca6f4f9f153e7b1beafed1d23304b7a39f9f3ff067d8d8f9e24ecc7",
+        );
+        assert_eq!(ecb_encrypt(key, &plaintext), Ok(ciphertext.clone()));
+        assert_eq!(ecb_decrypt(key, &ciphertext), Ok(plaintext));
+    }
+
+    #[test]
+    fn test_ecb_in_place() {
+        let key: [u8; 16] = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+            .try_into()
+            .unwrap();
+        let mut buffer =
+            hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        assert_eq!(ecb_encrypt_in_place(key, &mut buffer), Ok(()));
+        assert_eq!(
+            buffer,
+            hex_to_bytes("3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf")
+        );
+        assert_eq!(ecb_decrypt_in_place(key, &mut buffer), Ok(()));
+        assert_eq!(
+            buffer,
+            hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
+        );
+        assert_eq!(
+            ecb_encrypt_in_place(key, &mut [0; 15]),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ecb_invalid_length() {
+        assert_eq!(ecb_encrypt([0u8; 16], &[0; 15]), Err(Error::InvalidLength));
+        assert_eq!(ecb_decrypt([0u8; 16], &[0; 17]), Err(Error::InvalidLength));
+    }
+}
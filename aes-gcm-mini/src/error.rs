@@ -0,0 +1,39 @@
+use core::fmt;
+
+/// Errors returned by the block cipher modes and the authentication APIs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The input length is not supported by the mode, e.g. it is not a multiple of the block size.
+    InvalidLength,
+    /// The PKCS#7 padding of a decrypted message is malformed.
+    InvalidPadding,
+    /// The input needs more blocks than the counter can address without wrapping around.
+    CounterOverflow,
+    /// The counter width is not between 1 and 128 bits.
+    InvalidCounterWidth,
+    /// The nonce length is not supported by the mode.
+    InvalidNonceLength,
+    /// The tag length is not supported by the mode.
+    InvalidTagLength,
+    /// The authentication tag does not match the data.
+    AuthenticationFailed,
+    /// The nonce sequence of a key is exhausted, or the key reached its invocation limit.
+    NoncesExhausted,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength => write!(f, "invalid input length"),
+            Error::InvalidPadding => write!(f, "invalid PKCS#7 padding"),
+            Error::CounterOverflow => write!(f, "counter space exhausted"),
+            Error::InvalidCounterWidth => write!(f, "invalid counter width"),
+            Error::InvalidNonceLength => write!(f, "invalid nonce length"),
+            Error::InvalidTagLength => write!(f, "invalid tag length"),
+            Error::AuthenticationFailed => write!(f, "authentication failed"),
+            Error::NoncesExhausted => write!(f, "no fresh nonce left for the key"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -1,70 +1,46 @@
-use crate::aes::aes;
-use crate::utils::{block_to_stream, stream_to_block, Block, Stream};
-
-// Starting with fixed size input
-// TODO: Make this work for arbitrary length input
-pub fn gctr(key: Stream, initial_counter_block: Block, plaintext: Stream) -> Stream {
-    let mut ciphertext: Block = [[0; 4]; 4];
-    let pt_blocks = [stream_to_block(plaintext)];
-    let mut counter_block = initial_counter_block;
-
-    for block in &pt_blocks {
-        // Encrypt counter block
-        let encrypted_counter_block = aes(counter_block, key);
-        // XOR with plaintext
-        ciphertext = add_cipher(&encrypted_counter_block, block);
-        // Update counter block
-        counter_block = increment_32(counter_block);
-    }
-
-    block_to_stream(ciphertext)
+use crate::aes::AesKey;
+use crate::profile::{measure, Phase};
+use crate::utils::{wipe, Stream};
+
+// GCTR encrypts an arbitrary length buffer in place, a trailing partial block uses the
+// leftmost bytes of its encrypted counter block.
+pub fn gctr<K: AesKey>(key: K, initial_counter_block: Stream, buffer: &mut [u8]) {
+    measure(Phase::Gctr, || {
+        let mut counter_block = initial_counter_block;
+        let mut encrypted_counter_block: Stream = [0; 16];
+
+        for chunk in buffer.chunks_mut(16) {
+            // Encrypt counter block
+            encrypted_counter_block = key.encrypt_stream(counter_block);
+            // XOR with input
+            for i in 0..chunk.len() {
+                chunk[i] ^= encrypted_counter_block[i];
+            }
+            // Update counter block
+            counter_block = increment_32(counter_block);
+        }
+        // The key stream is secret, and only partly used by a trailing partial block
+        wipe(&mut encrypted_counter_block);
+    })
 }
 
-pub(crate) fn increment_32(counter_block: Block) -> Block {
-    let mut stream = block_to_stream(counter_block);
+// Increments the rightmost 32 bits of the counter block, modulo 2^32
+pub(crate) fn increment_32(counter_block: Stream) -> Stream {
+    let mut stream = counter_block;
     let word = [stream[12], stream[13], stream[14], stream[15]];
-    let incremented = increment_word(&word);
-    stream[12..16].clone_from_slice(&incremented);
-    stream_to_block(stream)
+    stream[12..16].copy_from_slice(&increment_word(&word));
+    stream
 }
 
-fn add_cipher(state: &Block, key: &Block) -> Block {
-    let mut new_state: Block = [[0; 4]; 4];
-
-    for i in 0..4 {
-        for j in 0..4 {
-            new_state[i][j] = state[i][j] ^ key[i][j];
-        }
-    }
-
-    new_state
-}
-
-/// Carry adder on 4-byte words
+/// Adds one to a big endian 4-byte word, wrapping around
 fn increment_word(word: &[u8; 4]) -> [u8; 4] {
-    let mut incremented = [word[3], word[2], word[1], word[0]];
-    let mut carry = 1;
-
-    for i in 0..4 {
-        if incremented[i] == 0xFF {
-            incremented[i] = 0x00;
-        } else {
-            incremented[i] += carry;
-            carry = 0;
-        }
-    }
-
-    [
-        incremented[3],
-        incremented[2],
-        incremented[1],
-        incremented[0],
-    ]
+    u32::from_be_bytes(*word).wrapping_add(1).to_be_bytes()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{block_to_stream, Block};
 
     #[test]
     fn test_increment_word() {
@@ -80,6 +56,10 @@ mod tests {
         let expected_incremented_word = [0x00, 0x01, 0x00, 0x00];
         assert_eq!(increment_word(&word), expected_incremented_word);
 
+        let word = [0x00, 0xFF, 0x00, 0x01];
+        let expected_incremented_word = [0x00, 0xFF, 0x00, 0x02];
+        assert_eq!(increment_word(&word), expected_incremented_word);
+
         let word = [0xFF, 0xFF, 0xFF, 0xFF];
         let expected_incremented_word = [0x00, 0x00, 0x00, 0x00];
         assert_eq!(increment_word(&word), expected_incremented_word);
@@ -105,7 +85,8 @@ mod tests {
             0xfd, 0xb4, 0xaa, 0xfa, 0x35, 0x19, 0xd3, 0xc0, 0x55, 0xbe, 0x8b, 0x34, 0x77, 0x64,
             0xea, 0x33,
         ];
-        let ciphertext = gctr(key, initial_counter_block, plaintext);
+        let mut ciphertext = plaintext;
+        gctr(key, block_to_stream(initial_counter_block), &mut ciphertext);
         assert_eq!(ciphertext, expected_ciphertext);
     }
 }
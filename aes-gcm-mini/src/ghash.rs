@@ -1,4 +1,7 @@
-use crate::utils::{bits_to_stream, bitwise_right_shift, stream_to_bits, stream_xor, Stream};
+use crate::ct;
+use crate::karatsuba;
+use crate::profile::{measure, Phase};
+use crate::utils::{bits_to_stream, bitwise_right_shift, stream_to_bits, stream_xor, wipe, Stream};
 
 // GHASH computes the authentication tag for AES-GCM.
 // Inputs:
@@ -31,16 +34,26 @@ use crate::utils::{bits_to_stream, bitwise_right_shift, stream_to_bits, stream_x
 //      │  TAG1   │ ─────┘      │   TAG2  │ ──────┘      │   TAGM  │
 //      └─────────┘             └─────────┘              └─────────┘
 
-// TODO: handle arbitrary number of blocks
-pub fn ghash<const N: usize>(hash_key: Stream, message: [Stream; N]) -> Stream {
+pub fn ghash(hash_key: Stream, message: &[Stream]) -> Stream {
     let mut tag: Stream = [0; 16];
-    for i in 0..N {
-        tag = stream_xor(tag, message[i]);
+    for block in message {
+        tag = stream_xor(tag, *block);
         tag = gmul(hash_key, tag);
     }
     tag
 }
 
+// GHASH over bytes split into zero padded blocks, continuing from `tag`, so that
+// the input does not have to be copied into blocks first
+pub(crate) fn ghash_padded(hash_key: Stream, mut tag: Stream, bytes: &[u8]) -> Stream {
+    for chunk in bytes.chunks(16) {
+        let mut block: Stream = [0; 16];
+        block[..chunk.len()].copy_from_slice(chunk);
+        tag = ghash(hash_key, &[stream_xor(tag, block)]);
+    }
+    tag
+}
+
 #[test]
 fn ghash_01() {
     // https://datatracker.ietf.org/doc/html/rfc8452#appendix-A
@@ -63,21 +76,38 @@ fn ghash_01() {
         0x7a,
     ];
 
-    assert_eq!(ghash(hash_key, message), expected);
+    assert_eq!(ghash(hash_key, &message), expected);
 }
 
-fn gmul(x: Stream, y: Stream) -> Stream {
+pub(crate) fn gmul(x: Stream, y: Stream) -> Stream {
+    measure(Phase::Gmul, || {
+        if cfg!(feature = "constant-time") {
+            return ct::gmul(x, y);
+        }
+        if cfg!(feature = "karatsuba") {
+            return karatsuba::gmul(x, y);
+        }
+        bitwise_gmul(x, y)
+    })
+}
+
+// The default GHASH multiplication, bit by bit, which the other backends are tested against
+pub(crate) fn bitwise_gmul(x: Stream, y: Stream) -> Stream {
     let mut result: Stream = [0; 16];
     let mut accumulator: Stream = y;
-    let x_bits = stream_to_bits(x);
+    let mut x_bits = stream_to_bits(x);
 
-    for i in 0..128 {
-        if x_bits[i] == 1 {
+    for bit in x_bits {
+        if bit == 1 {
             result = stream_xor(result, accumulator);
         }
-        accumulator = mulx(accumulator);
+        accumulator = bitwise_mulx(accumulator);
     }
 
+    // The bits are those of the hash key, and the accumulator is the hashed block times
+    // powers of x
+    wipe(&mut x_bits);
+    wipe(&mut accumulator);
     result
 }
 
@@ -137,7 +167,16 @@ fn gmul02() {
 /// if the msb is one, then the 8 most LSBs are XORed with 0xE1
 /// this 0xE1 is the hex representation of 11100001, which represents
 /// the polynomial 1 + x + x^2 + x^7
-fn mulx(block: Stream) -> Stream {
+#[cfg(feature = "alloc")]
+pub(crate) fn mulx(block: Stream) -> Stream {
+    if cfg!(feature = "constant-time") {
+        return ct::mulx(block);
+    }
+    bitwise_mulx(block)
+}
+
+// The default multiplication by x, on the bits of the block
+pub(crate) fn bitwise_mulx(block: Stream) -> Stream {
     let bits = stream_to_bits(block);
     let result_bits = bitwise_right_shift(bits);
     let mut bytes = bits_to_stream(result_bits);
@@ -159,5 +198,7 @@ fn test_mulx() {
         0xE1, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         0x00,
     ];
+    assert_eq!(bitwise_mulx(input), expected);
+    #[cfg(feature = "alloc")]
     assert_eq!(mulx(input), expected);
 }
@@ -0,0 +1,109 @@
+use crate::aes::AesKey;
+use crate::aes_gcm::aes_gcm_encrypt_in_place;
+use crate::error::Error;
+use crate::utils::{ct_eq, Stream};
+
+// AES-GMAC, the AES-GCM tag over the AAD with an empty plaintext. It authenticates the data
+// without encrypting it.
+pub fn gmac<K: AesKey>(key: K, iv: [u8; 12], aad: &[u8]) -> Stream {
+    aes_gcm_encrypt_in_place(key, iv, aad, &mut [])
+}
+
+// Checks a GMAC tag in constant time
+pub fn gmac_verify<K: AesKey>(key: K, iv: [u8; 12], aad: &[u8], tag: &Stream) -> Result<(), Error> {
+    if ct_eq(&gmac(key, iv, aad), tag) {
+        Ok(())
+    } else {
+        Err(Error::AuthenticationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    // GCM specification test cases 1 and 13, which have neither plaintext nor AAD
+    #[test]
+    fn test_gmac_empty() {
+        let tag = hex_to_bytes("58e2fccefa7e3061367f1d57a4e7455a");
+        assert_eq!(gmac([0u8; 16], [0; 12], &[]).to_vec(), tag);
+
+        let tag = hex_to_bytes("530f8afbc74536b9a963b4f1c4cb738b");
+        assert_eq!(gmac([0u8; 32], [0; 12], &[]).to_vec(), tag);
+    }
+
+    // IEEE 802.1AE GCM-AES test vectors, 2.1.1 54-byte packet authentication
+    #[test]
+    fn test_gmac() {
+        let iv: [u8; 12] = hex_to_bytes("12153524c0895e81b2c28465").try_into().unwrap();
+        let aad = hex_to_bytes(
+            "d609b1f056637a0d46df998d88e5222ab2c2846512153524c0895e8108000f10\
+             1112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f30\
+             313233340001",
+        );
+
+        let key: [u8; 16] = hex_to_bytes("ad7a2bd03eac835a6f620fdcb506b345")
+            .try_into()
+            .unwrap();
+        let tag: Stream = hex_to_bytes("f09478a9b09007d06f46e9b6a1da25dd")
+            .try_into()
+            .unwrap();
+        assert_eq!(gmac(key, iv, &aad), tag);
+        assert_eq!(gmac_verify(key, iv, &aad, &tag), Ok(()));
+
+        let key: [u8; 32] =
+            hex_to_bytes("e3c08a8f06c6e3ad95a70557b23f75483ce33021a9c72b7025666204c69c0b72")
+                .try_into()
+                .unwrap();
+        let tag: Stream = hex_to_bytes("2f0bc5af409e06d609ea8b7d0fa5ea50")
+            .try_into()
+            .unwrap();
+        assert_eq!(gmac(key, iv, &aad), tag);
+        assert_eq!(gmac_verify(key, iv, &aad, &tag), Ok(()));
+    }
+
+    // ESP packet with ENCR_NULL_AUTH_AES_GMAC (RFC 4543) from draft-mcgrew-gcm-test-01. The
+    // nonce is the salt followed by the explicit IV, and the SPI, sequence number, IV and
+    // payload are all authenticated as AAD.
+    #[test]
+    fn test_gmac_rfc4543() {
+        let key: [u8; 16] = hex_to_bytes("4c80cdefbb5d10da906ac73c3613a634")
+            .try_into()
+            .unwrap();
+        let nonce: [u8; 12] = hex_to_bytes("22433c640000000000000000").try_into().unwrap();
+        let aad = hex_to_bytes(
+            "00004321000000070000000000000000\
+             45000030da3a00008001df3bc0a80005c0a800010800c6cd020007006162636465666768\
+             696a6b6c6d6e6f707172737401020201",
+        );
+        let tag: Stream = hex_to_bytes("f2a9a836e155106aa8dcd618e4099aaa")
+            .try_into()
+            .unwrap();
+        assert_eq!(gmac(key, nonce, &aad), tag);
+        assert_eq!(gmac_verify(key, nonce, &aad, &tag), Ok(()));
+    }
+
+    #[test]
+    fn test_gmac_verify_rejects() {
+        let key = [0x42u8; 16];
+        let iv = [0x24; 12];
+        let aad = b"authenticated, not encrypted";
+        let tag = gmac(key, iv, aad);
+
+        let mut forged_tag = tag;
+        forged_tag[15] ^= 1;
+        assert_eq!(
+            gmac_verify(key, iv, aad, &forged_tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            gmac_verify(key, iv, b"authenticated, not encrypted!", &tag),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            gmac_verify(key, [0x25; 12], aad, &tag),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! GHASH multiplication as a carry-less product of 32-bit words, selected with the `karatsuba`
+//! feature.
+//!
+//! RV32IM has no carry-less multiply instruction, but its integer `mul` and `mulhu` still do
+//! most of the work when every operand is spread over four integers with three zero bits
+//! between the bits that are kept, as the carries then never reach a kept bit. Karatsuba's
+//! decomposition brings a 128-bit product down to nine 32-bit ones instead of sixteen, and the
+//! 256-bit result is reduced modulo x^128 + x^7 + x^2 + x + 1 with shifts. There are no
+//! branches or lookups on the operands, but `constant-time` still takes precedence over it as
+//! integer multiplication is not constant-time on every CPU.
+
+use crate::utils::{wipe, Stream};
+
+// GHASH multiplication, the bits of a block are the coefficients of x^0 to x^127 from the most
+// significant bit of its first byte on, so the blocks are read reversed
+pub(crate) fn gmul(x: Stream, y: Stream) -> Stream {
+    let mut x = u128::from_be_bytes(x).reverse_bits();
+    let mut y = u128::from_be_bytes(y).reverse_bits();
+    let (mut low, mut high) = clmul128(x, y);
+    let result = reduce(low, high).reverse_bits().to_be_bytes();
+
+    // One of the operands is the hash key, and the product is the hashed block times it
+    wipe(&mut x);
+    wipe(&mut y);
+    wipe(&mut low);
+    wipe(&mut high);
+    result
+}
+
+// Carry-less product of 32-bit words, the bits of the operands are split four ways so that the
+// up to eight partial products summed in a column carry into the three bits above it only
+fn clmul32(x: u32, y: u32) -> u64 {
+    let x0 = (x & 0x11111111) as u64;
+    let x1 = (x & 0x22222222) as u64;
+    let x2 = (x & 0x44444444) as u64;
+    let x3 = (x & 0x88888888) as u64;
+    let y0 = (y & 0x11111111) as u64;
+    let y1 = (y & 0x22222222) as u64;
+    let y2 = (y & 0x44444444) as u64;
+    let y3 = (y & 0x88888888) as u64;
+
+    let z0 = (x0 * y0) ^ (x1 * y3) ^ (x2 * y2) ^ (x3 * y1);
+    let z1 = (x0 * y1) ^ (x1 * y0) ^ (x2 * y3) ^ (x3 * y2);
+    let z2 = (x0 * y2) ^ (x1 * y1) ^ (x2 * y0) ^ (x3 * y3);
+    let z3 = (x0 * y3) ^ (x1 * y2) ^ (x2 * y1) ^ (x3 * y0);
+
+    (z0 & 0x1111111111111111)
+        | (z1 & 0x2222222222222222)
+        | (z2 & 0x4444444444444444)
+        | (z3 & 0x8888888888888888)
+}
+
+// Karatsuba on 64-bit halves, three 32-bit products instead of four
+fn clmul64(x: u64, y: u64) -> u128 {
+    let (x0, x1) = (x as u32, (x >> 32) as u32);
+    let (y0, y1) = (y as u32, (y >> 32) as u32);
+    let low = clmul32(x0, y0);
+    let high = clmul32(x1, y1);
+    let middle = clmul32(x0 ^ x1, y0 ^ y1) ^ low ^ high;
+    (low as u128) ^ ((middle as u128) << 32) ^ ((high as u128) << 64)
+}
+
+// Karatsuba on 128-bit halves, returns the low and high 128 bits of the product
+fn clmul128(x: u128, y: u128) -> (u128, u128) {
+    let (x0, x1) = (x as u64, (x >> 64) as u64);
+    let (y0, y1) = (y as u64, (y >> 64) as u64);
+    let low = clmul64(x0, y0);
+    let high = clmul64(x1, y1);
+    let middle = clmul64(x0 ^ x1, y0 ^ y1) ^ low ^ high;
+    (low ^ (middle << 64), high ^ (middle >> 64))
+}
+
+// Reduction modulo x^128 + x^7 + x^2 + x + 1, folding x^128 into x^7 + x^2 + x + 1 twice as the
+// first fold spills up to seven bits past x^127
+fn reduce(low: u128, high: u128) -> u128 {
+    let folded = high ^ (high << 1) ^ (high << 2) ^ (high << 7);
+    let spilled = (high >> 127) ^ (high >> 126) ^ (high >> 121);
+    low ^ folded ^ spilled ^ (spilled << 1) ^ (spilled << 2) ^ (spilled << 7)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ghash::bitwise_gmul;
+    use crate::utils::{hex_to_bytes, Rng};
+
+    #[test]
+    fn test_clmul32() {
+        assert_eq!(clmul32(0xffffffff, 0xffffffff), 0x5555555555555555);
+        assert_eq!(clmul32(0x80000000, 0x80000000), 1 << 62);
+        assert_eq!(clmul32(0b1011, 0b110), 0b111010);
+
+        // Schoolbook carry-less multiplication
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        for _ in 0..1000 {
+            let state = rng.next_u64();
+            let (x, y) = (state as u32, (state >> 32) as u32);
+            let mut expected = 0u64;
+            for i in 0..32 {
+                if (y >> i) & 1 == 1 {
+                    expected ^= (x as u64) << i;
+                }
+            }
+            assert_eq!(clmul32(x, y), expected);
+        }
+    }
+
+    #[test]
+    fn test_gmul() {
+        // The bitwise multiplication in `ghash` is the reference
+        let mut rng = Rng(0x9e3779b97f4a7c15);
+        for _ in 0..1000 {
+            let (x, y) = (rng.stream(), rng.stream());
+            assert_eq!(gmul(x, y), bitwise_gmul(x, y));
+        }
+
+        // The reduction of the largest possible product
+        let ones = [0xff; 16];
+        assert_eq!(gmul(ones, ones), bitwise_gmul(ones, ones));
+
+        let x: Stream = hex_to_bytes("aae06992acbf52a3e8f4a96ec9300bd7")
+            .try_into()
+            .unwrap();
+        let y: Stream = hex_to_bytes("98e7247c07f0fe411c267e4384b0f600")
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            gmul(x, y).to_vec(),
+            hex_to_bytes("90e87315fb7d4e1b4092ec0cbfda5d7d")
+        );
+    }
+}
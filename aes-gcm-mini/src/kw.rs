@@ -0,0 +1,287 @@
+use crate::aes::AesKey;
+use crate::error::Error;
+use crate::utils::{ct_eq, wipe, Stream};
+use alloc::vec::Vec;
+
+// Initial value of AES-KW (RFC 3394, section 2.2.3.1)
+const DEFAULT_IV: [u8; 8] = [0xA6; 8];
+
+// Prefix of the alternative initial value of AES-KWP, followed by the length of the key data
+// (RFC 5649, section 3)
+const ALTERNATIVE_IV_PREFIX: [u8; 4] = [0xA6, 0x59, 0x59, 0xA6];
+
+// AES Key Wrap (RFC 3394), the key data is a multiple of 8 bytes and at least 16 bytes long
+pub fn kw_wrap<K: AesKey>(kek: K, key_data: &[u8]) -> Result<Vec<u8>, Error> {
+    if key_data.len() % 8 != 0 || key_data.len() < 16 {
+        return Err(Error::InvalidLength);
+    }
+    Ok(wrap_semiblocks(kek, DEFAULT_IV, key_data))
+}
+
+// AES Key Unwrap (RFC 3394), the key data is only returned if the integrity check value
+// matches
+pub fn kw_unwrap<K: AesKey>(kek: K, wrapped_key: &[u8]) -> Result<Vec<u8>, Error> {
+    if wrapped_key.len() % 8 != 0 || wrapped_key.len() < 24 {
+        return Err(Error::InvalidLength);
+    }
+    let (iv, mut key_data) = unwrap_semiblocks(kek, wrapped_key);
+    if ct_eq(&iv, &DEFAULT_IV) {
+        Ok(key_data)
+    } else {
+        // The key data failed the integrity check, but it is still the decrypted key
+        wipe(&mut key_data[..]);
+        Err(Error::AuthenticationFailed)
+    }
+}
+
+// AES Key Wrap with Padding (RFC 5649), for key data of any length between 1 and 2^32 - 1
+// bytes
+pub fn kwp_wrap<K: AesKey>(kek: K, key_data: &[u8]) -> Result<Vec<u8>, Error> {
+    if key_data.is_empty() || key_data.len() > u32::MAX as usize {
+        return Err(Error::InvalidLength);
+    }
+    let mut iv = [0; 8];
+    iv[..4].copy_from_slice(&ALTERNATIVE_IV_PREFIX);
+    iv[4..].copy_from_slice(&(key_data.len() as u32).to_be_bytes());
+
+    let mut padded_key_data = key_data.to_vec();
+    padded_key_data.resize(key_data.len().div_ceil(8) * 8, 0);
+
+    // A single semiblock is encrypted together with the initial value in one block
+    if padded_key_data.len() == 8 {
+        let mut block: Stream = [0; 16];
+        block[..8].copy_from_slice(&iv);
+        block[8..].copy_from_slice(&padded_key_data);
+        return Ok(kek.encrypt_stream(block).to_vec());
+    }
+    Ok(wrap_semiblocks(kek, iv, &padded_key_data))
+}
+
+// AES Key Unwrap with Padding (RFC 5649), the key data is only returned if the initial value
+// and the padding are valid
+pub fn kwp_unwrap<K: AesKey>(kek: K, wrapped_key: &[u8]) -> Result<Vec<u8>, Error> {
+    if wrapped_key.len() % 8 != 0 || wrapped_key.len() < 16 {
+        return Err(Error::InvalidLength);
+    }
+    let (iv, mut padded_key_data) = if wrapped_key.len() == 16 {
+        let mut block: Stream = [0; 16];
+        block.copy_from_slice(wrapped_key);
+        let mut block = kek.decrypt_stream(block);
+        let mut iv = [0; 8];
+        iv.copy_from_slice(&block[..8]);
+        let padded_key_data = block[8..].to_vec();
+        wipe(&mut block);
+        (iv, padded_key_data)
+    } else {
+        unwrap_semiblocks(kek, wrapped_key)
+    };
+
+    // The length has to fall in the last semiblock and the padding has to be zero
+    let key_data_len = u32::from_be_bytes([iv[4], iv[5], iv[6], iv[7]]) as usize;
+    let padded_len = padded_key_data.len();
+    let mut is_valid = ct_eq(&iv[..4], &ALTERNATIVE_IV_PREFIX)
+        && key_data_len + 8 > padded_len
+        && key_data_len <= padded_len;
+    if is_valid {
+        let mut padding = 0;
+        for byte in &padded_key_data[key_data_len..padded_len] {
+            padding |= byte;
+        }
+        is_valid = padding == 0;
+    }
+
+    if is_valid {
+        padded_key_data.truncate(key_data_len);
+        Ok(padded_key_data)
+    } else {
+        wipe(&mut padded_key_data[..]);
+        Err(Error::AuthenticationFailed)
+    }
+}
+
+// The wrapping function W of SP 800-38F, 6 rounds over n semiblocks of 8 bytes, each step
+// encrypting the register A with one semiblock and XORing A with the step counter t
+fn wrap_semiblocks<K: AesKey>(kek: K, iv: [u8; 8], plaintext: &[u8]) -> Vec<u8> {
+    let n = plaintext.len() / 8;
+    let mut a = iv;
+    let mut r = plaintext.to_vec();
+
+    for j in 0..6 {
+        for i in 0..n {
+            let mut block: Stream = [0; 16];
+            block[..8].copy_from_slice(&a);
+            block[8..].copy_from_slice(&r[i * 8..(i + 1) * 8]);
+            let block = kek.encrypt_stream(block);
+
+            let t = ((n * j + i + 1) as u64).to_be_bytes();
+            for k in 0..8 {
+                a[k] = block[k] ^ t[k];
+            }
+            r[i * 8..(i + 1) * 8].copy_from_slice(&block[8..]);
+        }
+    }
+
+    let mut ciphertext = a.to_vec();
+    ciphertext.extend(r);
+    ciphertext
+}
+
+// The unwrapping function W^-1 of SP 800-38F, returns the register A and the semiblocks
+fn unwrap_semiblocks<K: AesKey>(kek: K, ciphertext: &[u8]) -> ([u8; 8], Vec<u8>) {
+    let n = ciphertext.len() / 8 - 1;
+    let mut a = [0; 8];
+    a.copy_from_slice(&ciphertext[..8]);
+    let mut r = ciphertext[8..].to_vec();
+
+    for j in (0..6).rev() {
+        for i in (0..n).rev() {
+            let t = ((n * j + i + 1) as u64).to_be_bytes();
+            let mut block: Stream = [0; 16];
+            for k in 0..8 {
+                block[k] = a[k] ^ t[k];
+            }
+            block[8..].copy_from_slice(&r[i * 8..(i + 1) * 8]);
+            let block = kek.decrypt_stream(block);
+
+            a.copy_from_slice(&block[..8]);
+            r[i * 8..(i + 1) * 8].copy_from_slice(&block[8..]);
+        }
+    }
+
+    (a, r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    fn check_kw<K: AesKey>(kek: K, key_data: &str, wrapped_key: &str) {
+        let key_data = hex_to_bytes(key_data);
+        let wrapped_key = hex_to_bytes(wrapped_key);
+        assert_eq!(kw_wrap(kek, &key_data), Ok(wrapped_key.clone()));
+        assert_eq!(kw_unwrap(kek, &wrapped_key), Ok(key_data));
+    }
+
+    // RFC 3394, section 4
+    #[test]
+    fn test_kw_rfc_3394() {
+        let kek_128: [u8; 16] = hex_to_bytes("000102030405060708090a0b0c0d0e0f")
+            .try_into()
+            .unwrap();
+        let kek_192: [u8; 24] = hex_to_bytes("000102030405060708090a0b0c0d0e0f1011121314151617")
+            .try_into()
+            .unwrap();
+        let kek_256: [u8; 32] =
+            hex_to_bytes("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")
+                .try_into()
+                .unwrap();
+
+        // 4.1 to 4.3, 128 bits of key data
+        let key_data = "00112233445566778899aabbccddeeff";
+        check_kw(
+            kek_128,
+            key_data,
+            "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5",
+        );
+        check_kw(
+            kek_192,
+            key_data,
+            "96778b25ae6ca435f92b5b97c050aed2468ab8a17ad84e5d",
+        );
+        check_kw(
+            kek_256,
+            key_data,
+            "64e8c3f9ce0f5ba263e9777905818a2a93c8191e7d6e8ae7",
+        );
+
+        // 4.4 and 4.5, 192 bits of key data
+        let key_data = "00112233445566778899aabbccddeeff0001020304050607";
+        check_kw(
+            kek_192,
+            key_data,
+            "031d33264e15d33268f24ec260743edce1c6c7ddee725a936ba814915c6762d2",
+        );
+        check_kw(
+            kek_256,
+            key_data,
+            "a8f9bc1612c68b3ff6e6f4fbe30e71e4769c8b80a32cb8958cd5d17d6b254da1",
+        );
+
+        // 4.6, 256 bits of key data
+        check_kw(
+            kek_256,
+            "00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f",
+            "28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43b\
+             fb988b9b7a02dd21",
+        );
+    }
+
+    #[test]
+    fn test_kw_errors() {
+        let kek = [0x42u8; 16];
+        assert_eq!(kw_wrap(kek, &[0; 8]), Err(Error::InvalidLength));
+        assert_eq!(kw_wrap(kek, &[0; 20]), Err(Error::InvalidLength));
+        assert_eq!(kw_unwrap(kek, &[0; 16]), Err(Error::InvalidLength));
+
+        let mut wrapped_key = kw_wrap(kek, &[0x11; 32]).unwrap();
+        wrapped_key[20] ^= 1;
+        assert_eq!(
+            kw_unwrap(kek, &wrapped_key),
+            Err(Error::AuthenticationFailed)
+        );
+        let wrapped_key = kw_wrap(kek, &[0x11; 32]).unwrap();
+        assert_eq!(
+            kw_unwrap([0x43u8; 16], &wrapped_key),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+
+    // RFC 5649, section 6
+    #[test]
+    fn test_kwp_rfc_5649() {
+        let kek: [u8; 24] = hex_to_bytes("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8")
+            .try_into()
+            .unwrap();
+
+        let key_data = hex_to_bytes("c37b7e6492584340bed12207808941155068f738");
+        let wrapped_key =
+            hex_to_bytes("138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a");
+        assert_eq!(kwp_wrap(kek, &key_data), Ok(wrapped_key.clone()));
+        assert_eq!(kwp_unwrap(kek, &wrapped_key), Ok(key_data));
+
+        let key_data = hex_to_bytes("466f7250617369");
+        let wrapped_key = hex_to_bytes("afbeb0f07dfbf5419200f2ccb50bb24f");
+        assert_eq!(kwp_wrap(kek, &key_data), Ok(wrapped_key.clone()));
+        assert_eq!(kwp_unwrap(kek, &wrapped_key), Ok(key_data));
+    }
+
+    #[test]
+    fn test_kwp_errors() {
+        let kek = [0x42u8; 32];
+        assert_eq!(kwp_wrap(kek, &[]), Err(Error::InvalidLength));
+        assert_eq!(kwp_unwrap(kek, &[0; 8]), Err(Error::InvalidLength));
+        assert_eq!(kwp_unwrap(kek, &[0; 20]), Err(Error::InvalidLength));
+
+        for len in [1, 8, 9, 33] {
+            let key_data = vec![0x11; len];
+            let wrapped_key = kwp_wrap(kek, &key_data).unwrap();
+            assert_eq!(wrapped_key.len(), len.div_ceil(8) * 8 + 8);
+            assert_eq!(kwp_unwrap(kek, &wrapped_key), Ok(key_data));
+
+            let mut forged_wrapped_key = wrapped_key.clone();
+            forged_wrapped_key[len] ^= 1;
+            assert_eq!(
+                kwp_unwrap(kek, &forged_wrapped_key),
+                Err(Error::AuthenticationFailed)
+            );
+        }
+
+        // A plain AES-KW wrapped key does not carry the alternative initial value
+        let wrapped_key = kw_wrap(kek, &[0x11; 16]).unwrap();
+        assert_eq!(
+            kwp_unwrap(kek, &wrapped_key),
+            Err(Error::AuthenticationFailed)
+        );
+    }
+}
@@ -1,9 +1,74 @@
+// The in-place APIs only need `core`, the `alloc` feature adds the ones returning a `Vec` and
+// the modes that cannot work in place
+#![cfg_attr(not(test), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 mod aes;
 mod aes_gcm;
+#[cfg(feature = "alloc")]
+mod aes_gcm_siv;
+mod cbc;
+mod ccm;
+mod cmac;
+mod ct;
+mod ctr;
+mod ecb;
+mod error;
 mod gctr;
 mod ghash;
+mod gmac;
+mod karatsuba;
+#[cfg(feature = "alloc")]
+mod kw;
+mod nonce;
+#[cfg(feature = "alloc")]
+mod polyval;
+pub mod profile;
+#[cfg(feature = "alloc")]
+mod siv;
+mod t_table;
 mod utils;
+mod xts;
 
-pub use aes::aes;
-pub use aes_gcm::aes_gcm;
-pub use utils::{Block, Stream};
+pub use aes::{
+    aes, aes192, aes192_inv, aes256, aes256_inv, aes_inv, aes_t_table, Aes128, Aes192, Aes256,
+    AesCipher, AesKey,
+};
+pub use aes_gcm::{
+    aes_gcm, aes_gcm_decrypt_in_place, aes_gcm_decrypt_in_place_truncated,
+    aes_gcm_encrypt_in_place, aes_gcm_encrypt_in_place_truncated, AesGcm,
+};
+#[cfg(feature = "alloc")]
+pub use aes_gcm::{
+    aes_gcm_decrypt, aes_gcm_decrypt_truncated, aes_gcm_encrypt, aes_gcm_encrypt_truncated,
+};
+#[cfg(feature = "alloc")]
+pub use aes_gcm_siv::{aes_gcm_siv_decrypt, aes_gcm_siv_encrypt, AesGcmSivKey};
+#[cfg(feature = "alloc")]
+pub use cbc::{cbc_decrypt, cbc_decrypt_pkcs7, cbc_encrypt, cbc_encrypt_pkcs7, pkcs7_pad};
+pub use cbc::{cbc_decrypt_in_place, cbc_encrypt_in_place, pkcs7_unpad};
+#[cfg(feature = "alloc")]
+pub use ccm::{ccm_decrypt, ccm_encrypt};
+pub use ccm::{ccm_decrypt_in_place, ccm_encrypt_in_place};
+pub use cmac::{cmac, Cmac};
+#[cfg(feature = "alloc")]
+pub use ctr::ctr;
+pub use ctr::ctr_in_place;
+#[cfg(feature = "alloc")]
+pub use ecb::{ecb_decrypt, ecb_encrypt};
+pub use ecb::{ecb_decrypt_in_place, ecb_encrypt_in_place};
+pub use error::Error;
+pub use gmac::{gmac, gmac_verify};
+#[cfg(feature = "alloc")]
+pub use kw::{kw_unwrap, kw_wrap, kwp_unwrap, kwp_wrap};
+pub use nonce::{CounterNonce, NonceSequence, SealingKey, Tls13Nonce};
+#[cfg(feature = "alloc")]
+pub use siv::{siv_decrypt, siv_encrypt};
+pub use utils::{block_to_stream, stream_to_block, Block, Stream};
+#[cfg(feature = "alloc")]
+pub use xts::{xts_decrypt, xts_encrypt};
+pub use xts::{xts_decrypt_in_place, xts_encrypt_in_place, XtsKey};
@@ -0,0 +1,273 @@
+use crate::aes_gcm::AesGcm;
+use crate::error::Error;
+use crate::utils::Stream;
+
+/// A sequence of 96-bit IVs that never repeats under the key it is used with.
+pub trait NonceSequence {
+    /// Returns the next IV, or [`Error::NoncesExhausted`] once the sequence would repeat.
+    fn advance(&mut self) -> Result<[u8; 12], Error>;
+}
+
+/// The deterministic construction of SP 800-38D, 8.2.1: a 32-bit fixed field that tells
+/// apart the devices or contexts sharing a key, followed by a 64-bit invocation counter.
+///
+/// The counter has to be persisted across restarts: [`CounterNonce::counter`] gives the value
+/// to store, and [`CounterNonce::starting_at`] resumes from it.
+#[derive(Clone, Debug)]
+pub struct CounterNonce {
+    fixed: [u8; 4],
+    counter: Option<u64>,
+}
+
+impl CounterNonce {
+    pub fn new(fixed: [u8; 4]) -> Self {
+        Self::starting_at(fixed, 0)
+    }
+
+    pub fn starting_at(fixed: [u8; 4], counter: u64) -> Self {
+        Self {
+            fixed,
+            counter: Some(counter),
+        }
+    }
+
+    /// The invocation counter of the next IV, or `None` once the counter is exhausted.
+    pub fn counter(&self) -> Option<u64> {
+        self.counter
+    }
+}
+
+impl NonceSequence for CounterNonce {
+    fn advance(&mut self) -> Result<[u8; 12], Error> {
+        let counter = self.counter.ok_or(Error::NoncesExhausted)?;
+        let mut iv = [0; 12];
+        iv[..4].copy_from_slice(&self.fixed);
+        iv[4..].copy_from_slice(&counter.to_be_bytes());
+        self.counter = counter.checked_add(1);
+        Ok(iv)
+    }
+}
+
+/// The per-record nonce of TLS 1.3 (RFC 8446, 5.3): the static IV XORed with the 64-bit
+/// record sequence number, left padded with zeros.
+///
+/// Like the counter of [`CounterNonce`], the sequence number is persisted with
+/// [`Tls13Nonce::sequence_number`] and resumed with [`Tls13Nonce::starting_at`].
+#[derive(Clone, Debug)]
+pub struct Tls13Nonce {
+    static_iv: [u8; 12],
+    sequence_number: Option<u64>,
+}
+
+impl Tls13Nonce {
+    pub fn new(static_iv: [u8; 12]) -> Self {
+        Self::starting_at(static_iv, 0)
+    }
+
+    pub fn starting_at(static_iv: [u8; 12], sequence_number: u64) -> Self {
+        Self {
+            static_iv,
+            sequence_number: Some(sequence_number),
+        }
+    }
+
+    /// The sequence number of the next record, or `None` once the sequence is exhausted.
+    pub fn sequence_number(&self) -> Option<u64> {
+        self.sequence_number
+    }
+}
+
+impl NonceSequence for Tls13Nonce {
+    fn advance(&mut self) -> Result<[u8; 12], Error> {
+        let sequence_number = self.sequence_number.ok_or(Error::NoncesExhausted)?;
+        let mut iv = self.static_iv;
+        let sequence_bytes = sequence_number.to_be_bytes();
+        for i in 0..8 {
+            iv[4 + i] ^= sequence_bytes[i];
+        }
+        self.sequence_number = sequence_number.checked_add(1);
+        Ok(iv)
+    }
+}
+
+// SP 800-38D, 8.3 only bounds the invocations to 2^32 for random IVs and IVs that are not 96
+// bits long. The deterministic sequences could go further, but every key is held to the same
+// bound whatever its sequence.
+const MAX_INVOCATIONS: u64 = 1 << 32;
+
+/// An AES-GCM key that takes its IVs from a nonce sequence, so that none is ever used twice.
+///
+/// It refuses to encrypt with [`Error::NoncesExhausted`] once the sequence is exhausted or
+/// after 2^32 encryptions, at which point the key has to be replaced. The limit counts the
+/// encryptions over the lifetime of the key, so [`SealingKey::invocations`] has to be persisted
+/// alongside the nonce sequence, and passed to [`SealingKey::starting_at`] on restart.
+pub struct SealingKey<N: NonceSequence, const W: usize> {
+    key: AesGcm<W>,
+    nonces: N,
+    invocations: u64,
+}
+
+impl<N: NonceSequence, const W: usize> SealingKey<N, W> {
+    pub fn new(key: AesGcm<W>, nonces: N) -> Self {
+        Self::starting_at(key, nonces, 0)
+    }
+
+    pub fn starting_at(key: AesGcm<W>, nonces: N, invocations: u64) -> Self {
+        Self {
+            key,
+            nonces,
+            invocations,
+        }
+    }
+
+    /// The nonce sequence the IVs are taken from, whose state has to be persisted.
+    pub fn nonces(&self) -> &N {
+        &self.nonces
+    }
+
+    /// The number of encryptions made under the key so far.
+    pub fn invocations(&self) -> u64 {
+        self.invocations
+    }
+
+    /// Encrypts `buffer` in place under the next IV, and returns that IV and the tag. The
+    /// buffer is left untouched if no IV is left.
+    pub fn seal_in_place_detached(
+        &mut self,
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<([u8; 12], Stream), Error> {
+        if self.invocations >= MAX_INVOCATIONS {
+            return Err(Error::NoncesExhausted);
+        }
+        let iv = self.nonces.advance()?;
+        self.invocations += 1;
+        let tag = self.key.encrypt_in_place_detached(iv, aad, buffer);
+        Ok((iv, tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::Aes128;
+    use crate::utils::hex_to_bytes;
+
+    #[test]
+    fn test_counter_nonce() {
+        let mut nonces = CounterNonce::new([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(
+            nonces.advance().unwrap().to_vec(),
+            hex_to_bytes("deadbeef0000000000000000")
+        );
+        assert_eq!(
+            nonces.advance().unwrap().to_vec(),
+            hex_to_bytes("deadbeef0000000000000001")
+        );
+        assert_eq!(nonces.counter(), Some(2));
+
+        let mut nonces = CounterNonce::starting_at([0xde, 0xad, 0xbe, 0xef], u64::MAX - 1);
+        assert_eq!(
+            nonces.advance().unwrap().to_vec(),
+            hex_to_bytes("deadbeeffffffffffffffffe")
+        );
+        assert_eq!(
+            nonces.advance().unwrap().to_vec(),
+            hex_to_bytes("deadbeefffffffffffffffff")
+        );
+        assert_eq!(nonces.counter(), None);
+        assert_eq!(nonces.advance(), Err(Error::NoncesExhausted));
+        assert_eq!(nonces.advance(), Err(Error::NoncesExhausted));
+    }
+
+    // The first record protected with the client handshake traffic key of RFC 8448, section 3
+    #[test]
+    fn test_tls13_nonce() {
+        let static_iv: [u8; 12] = hex_to_bytes("5d313eb2671276ee13000b30").try_into().unwrap();
+        let mut nonces = Tls13Nonce::new(static_iv);
+        assert_eq!(nonces.advance(), Ok(static_iv));
+        assert_eq!(
+            nonces.advance().unwrap().to_vec(),
+            hex_to_bytes("5d313eb2671276ee13000b31")
+        );
+        assert_eq!(nonces.sequence_number(), Some(2));
+
+        let mut nonces = Tls13Nonce::starting_at(static_iv, u64::MAX);
+        assert_eq!(
+            nonces.advance().unwrap().to_vec(),
+            hex_to_bytes("5d313eb298ed8911ecfff4cf")
+        );
+        assert_eq!(nonces.sequence_number(), None);
+        assert_eq!(nonces.advance(), Err(Error::NoncesExhausted));
+    }
+
+    #[test]
+    fn test_sealing_key() {
+        let key = [0x42u8; 16];
+        let fixed = [0x01, 0x02, 0x03, 0x04];
+        let mut sealing_key =
+            SealingKey::new(AesGcm::new(Aes128::new(key)), CounterNonce::new(fixed));
+        let plaintext = [0x5a; 40];
+
+        // Every message is encrypted under a fresh IV, which the receiver decrypts with
+        let mut previous_iv = None;
+        for _ in 0..3 {
+            let mut buffer = plaintext;
+            let (iv, tag) = sealing_key
+                .seal_in_place_detached(b"aad", &mut buffer)
+                .unwrap();
+            assert_ne!(Some(iv), previous_iv);
+            previous_iv = Some(iv);
+
+            let opening_key = AesGcm::new(Aes128::new(key));
+            assert_eq!(
+                opening_key.decrypt_in_place_detached(iv, b"aad", &mut buffer, &tag),
+                Ok(())
+            );
+            assert_eq!(buffer, plaintext);
+        }
+
+        // A key restored from its persisted state carries on where it stopped
+        assert_eq!(sealing_key.invocations(), 3);
+        let counter = sealing_key.nonces().counter().unwrap();
+        let mut restored_key = SealingKey::starting_at(
+            AesGcm::new(Aes128::new(key)),
+            CounterNonce::starting_at(fixed, counter),
+            sealing_key.invocations(),
+        );
+        let mut buffer = plaintext;
+        assert_eq!(
+            restored_key.seal_in_place_detached(&[], &mut buffer),
+            sealing_key.seal_in_place_detached(&[], &mut plaintext.clone())
+        );
+        assert_eq!(restored_key.invocations(), 4);
+
+        // The key gives up once the counter is exhausted
+        let mut sealing_key = SealingKey::new(
+            AesGcm::new(Aes128::new(key)),
+            CounterNonce::starting_at(fixed, u64::MAX),
+        );
+        let mut buffer = plaintext;
+        assert!(sealing_key.seal_in_place_detached(&[], &mut buffer).is_ok());
+        let mut buffer = plaintext;
+        assert_eq!(
+            sealing_key.seal_in_place_detached(&[], &mut buffer),
+            Err(Error::NoncesExhausted)
+        );
+        assert_eq!(buffer, plaintext);
+
+        // Or after 2^32 invocations, even though the counter has IVs left
+        let mut sealing_key = SealingKey::starting_at(
+            AesGcm::new(Aes128::new(key)),
+            CounterNonce::new(fixed),
+            MAX_INVOCATIONS - 1,
+        );
+        assert!(sealing_key.seal_in_place_detached(&[], &mut buffer).is_ok());
+        let mut buffer = plaintext;
+        assert_eq!(
+            sealing_key.seal_in_place_detached(&[], &mut buffer),
+            Err(Error::NoncesExhausted)
+        );
+        assert_eq!(buffer, plaintext);
+    }
+}
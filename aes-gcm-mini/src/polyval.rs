@@ -0,0 +1,50 @@
+use crate::ghash::{ghash, mulx};
+use crate::utils::Stream;
+use alloc::vec::Vec;
+
+// POLYVAL is the universal hash of AES-GCM-SIV. It works in the same field as GHASH but with
+// the bytes of every block in little endian order, so it is computed with GHASH as in
+// RFC 8452, Appendix A:
+// POLYVAL(H, X_1, ..., X_n) =
+//     ByteReverse(GHASH(mulX_GHASH(ByteReverse(H)), ByteReverse(X_1), ..., ByteReverse(X_n)))
+pub fn polyval(hash_key: Stream, message: &[Stream]) -> Stream {
+    let ghash_key = mulx(byte_reverse(hash_key));
+    let mut reversed_message = Vec::with_capacity(message.len());
+    for block in message {
+        reversed_message.push(byte_reverse(*block));
+    }
+    byte_reverse(ghash(ghash_key, &reversed_message))
+}
+
+fn byte_reverse(stream: Stream) -> Stream {
+    let mut reversed: Stream = [0; 16];
+    for i in 0..16 {
+        reversed[i] = stream[15 - i];
+    }
+    reversed
+}
+
+#[test]
+fn polyval_01() {
+    // https://datatracker.ietf.org/doc/html/rfc8452#appendix-A
+    let hash_key: Stream = [
+        0x25, 0x62, 0x93, 0x47, 0x58, 0x92, 0x42, 0x76, 0x1d, 0x31, 0xf8, 0x26, 0xba, 0x4b, 0x75,
+        0x7b,
+    ];
+    let message: [Stream; 2] = [
+        [
+            0x4f, 0x4f, 0x95, 0x66, 0x8c, 0x83, 0xdf, 0xb6, 0x40, 0x17, 0x62, 0xbb, 0x2d, 0x01,
+            0xa2, 0x62,
+        ],
+        [
+            0xd1, 0xa2, 0x4d, 0xdd, 0x27, 0x21, 0xd0, 0x06, 0xbb, 0xe4, 0x5f, 0x20, 0xd3, 0xc9,
+            0xf3, 0x62,
+        ],
+    ];
+    let expected: Stream = [
+        0xf7, 0xa3, 0xb4, 0x7b, 0x84, 0x61, 0x19, 0xfa, 0xe5, 0xb7, 0x86, 0x6c, 0xf5, 0xe5, 0xb7,
+        0x7e,
+    ];
+
+    assert_eq!(polyval(hash_key, &message), expected);
+}
@@ -0,0 +1,136 @@
+//! Cycle accounting for the AES-GCM primitives, enabled with the `profile` feature.
+//!
+//! The crate has no notion of cycles itself, so the caller installs a counter with
+//! [`set_cycle_counter`], e.g. `env::cycle_count` in a RISC Zero guest, and reads the totals
+//! back with [`report`]. Phases nest (`aes` runs inside `gctr`) and cycles are only charged
+//! to the innermost running phase, so the totals add up to the time spent in the primitives.
+
+/// Primitives whose cycles are accounted separately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    KeyExpansion,
+    Aes,
+    Gmul,
+    Gctr,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [Phase::KeyExpansion, Phase::Aes, Phase::Gmul, Phase::Gctr];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::KeyExpansion => "key_expansion",
+            Phase::Aes => "aes",
+            Phase::Gmul => "gmul",
+            Phase::Gctr => "gctr",
+        }
+    }
+}
+
+#[cfg(feature = "profile")]
+pub use profiler::{report, reset, set_cycle_counter};
+
+#[cfg(feature = "profile")]
+pub(crate) use profiler::measure;
+
+#[cfg(not(feature = "profile"))]
+#[inline(always)]
+pub(crate) fn measure<T>(_phase: Phase, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+#[cfg(feature = "profile")]
+mod profiler {
+    use super::Phase;
+    use std::cell::RefCell;
+    use std::thread_local;
+    use std::vec::Vec;
+
+    #[derive(Default)]
+    struct Profiler {
+        counter: Option<fn() -> u64>,
+        totals: [u64; 4],
+        running: Vec<Phase>,
+        last: u64,
+    }
+
+    impl Profiler {
+        // Charges the cycles since the last event to the innermost running phase
+        fn charge(&mut self) {
+            if let Some(counter) = self.counter {
+                let now = counter();
+                if let Some(&phase) = self.running.last() {
+                    self.totals[phase as usize] += now - self.last;
+                }
+                self.last = now;
+            }
+        }
+    }
+
+    // Profiling is per thread, a guest only has one
+    thread_local! {
+        static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::default());
+    }
+
+    /// Installs the cycle counter and clears the totals.
+    pub fn set_cycle_counter(counter: fn() -> u64) {
+        PROFILER.with_borrow_mut(|profiler| {
+            profiler.counter = Some(counter);
+            profiler.totals = [0; 4];
+        });
+    }
+
+    /// Clears the totals.
+    pub fn reset() {
+        PROFILER.with_borrow_mut(|profiler| profiler.totals = [0; 4]);
+    }
+
+    /// Cycles spent in each phase since the counter was installed or the last reset.
+    pub fn report() -> Vec<(Phase, u64)> {
+        PROFILER.with_borrow(|profiler| {
+            Phase::ALL
+                .iter()
+                .map(|&phase| (phase, profiler.totals[phase as usize]))
+                .collect()
+        })
+    }
+
+    pub(crate) fn measure<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+        PROFILER.with_borrow_mut(|profiler| {
+            profiler.charge();
+            profiler.running.push(phase);
+        });
+        let result = f();
+        PROFILER.with_borrow_mut(|profiler| {
+            profiler.charge();
+            profiler.running.pop();
+        });
+        result
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::cell::Cell;
+
+        thread_local! {
+            static CYCLES: Cell<u64> = const { Cell::new(0) };
+        }
+
+        fn tick() -> u64 {
+            CYCLES.replace(CYCLES.get() + 1)
+        }
+
+        #[test]
+        fn test_measure_nested() {
+            set_cycle_counter(tick);
+            measure(Phase::Gctr, || measure(Phase::Aes, || {}));
+            let report = report();
+            // Every call to the counter advances it by one, the inner phase is charged between
+            // its own entry and exit and the outer phase for the two transitions around it.
+            assert_eq!(report[Phase::Aes as usize], (Phase::Aes, 1));
+            assert_eq!(report[Phase::Gctr as usize], (Phase::Gctr, 2));
+            assert_eq!(report[Phase::Gmul as usize], (Phase::Gmul, 0));
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::aes::AesKey;
+use crate::cmac::{cmac, dbl};
+use crate::ctr::ctr;
+use crate::error::Error;
+use crate::utils::{ct_eq, stream_xor, wipe, Stream};
+use alloc::vec::Vec;
+
+// S2V accepts at most 126 associated data components besides the plaintext (RFC 5297,
+// section 2.6)
+const MAX_COMPONENTS: usize = 126;
+
+// AES-SIV encryption (RFC 5297) with any number of associated data components, e.g. a header
+// and a nonce, returns the ciphertext and the synthetic IV, which RFC 5297 places in front of
+// the ciphertext. Without a nonce the encryption is deterministic. The key is split into a
+// CMAC key and a CTR key of the same size, so AES-SIV-256 takes two 128-bit keys.
+pub fn siv_encrypt<K: AesKey>(
+    mac_key: K,
+    ctr_key: K,
+    associated_data: &[&[u8]],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Stream), Error> {
+    if associated_data.len() > MAX_COMPONENTS {
+        return Err(Error::InvalidLength);
+    }
+    let siv = s2v(mac_key, associated_data, plaintext);
+    let ciphertext = ctr(ctr_key, counter_block(siv), 128, plaintext)?;
+    Ok((ciphertext, siv))
+}
+
+// AES-SIV decryption, the plaintext is only returned if the synthetic IV is valid
+pub fn siv_decrypt<K: AesKey>(
+    mac_key: K,
+    ctr_key: K,
+    associated_data: &[&[u8]],
+    ciphertext: &[u8],
+    siv: &Stream,
+) -> Result<Vec<u8>, Error> {
+    if associated_data.len() > MAX_COMPONENTS {
+        return Err(Error::InvalidLength);
+    }
+    let mut plaintext = ctr(ctr_key, counter_block(*siv), 128, ciphertext)?;
+    if ct_eq(&s2v(mac_key, associated_data, &plaintext), siv) {
+        Ok(plaintext)
+    } else {
+        wipe(&mut plaintext[..]);
+        Err(Error::AuthenticationFailed)
+    }
+}
+
+// S2V turns a vector of strings into a single block with CMAC, the plaintext is the last
+// string:
+// D = CMAC(K, 0^128)
+// D = dbl(D) xor CMAC(K, S_i) for every associated data component S_i
+// V = CMAC(K, S_n xorend D) if S_n is at least a block long
+// V = CMAC(K, dbl(D) xor pad(S_n)) otherwise, where pad appends 10^i
+fn s2v<K: AesKey>(key: K, associated_data: &[&[u8]], plaintext: &[u8]) -> Stream {
+    let mut d = cmac(key, &[0; 16]);
+    for data in associated_data {
+        d = stream_xor(dbl(d), cmac(key, data));
+    }
+
+    if plaintext.len() >= 16 {
+        let mut t = plaintext.to_vec();
+        let offset = t.len() - 16;
+        for i in 0..16 {
+            t[offset + i] ^= d[i];
+        }
+        cmac(key, &t)
+    } else {
+        let mut padded: Stream = [0; 16];
+        padded[..plaintext.len()].copy_from_slice(plaintext);
+        padded[plaintext.len()] = 0x80;
+        cmac(key, &stream_xor(dbl(d), padded))
+    }
+}
+
+// The counter starts at the synthetic IV with the 31st and 63rd rightmost bits cleared, so
+// implementations can use 64 or 32-bit additions
+fn counter_block(siv: Stream) -> Stream {
+    let mut counter_block = siv;
+    counter_block[8] &= 0x7f;
+    counter_block[12] &= 0x7f;
+    counter_block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    // RFC 5297, appendix A.1, deterministic authenticated encryption
+    #[test]
+    fn test_siv_deterministic() {
+        let mac_key: [u8; 16] = hex_to_bytes("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0")
+            .try_into()
+            .unwrap();
+        let ctr_key: [u8; 16] = hex_to_bytes("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")
+            .try_into()
+            .unwrap();
+        let associated_data = hex_to_bytes("101112131415161718191a1b1c1d1e1f2021222324252627");
+        let plaintext = hex_to_bytes("112233445566778899aabbccddee");
+
+        let (ciphertext, siv) =
+            siv_encrypt(mac_key, ctr_key, &[&associated_data], &plaintext).unwrap();
+        assert_eq!(
+            siv.to_vec(),
+            hex_to_bytes("85632d07c6e8f37f950acd320a2ecc93")
+        );
+        assert_eq!(ciphertext, hex_to_bytes("40c02b9690c4dc04daef7f6afe5c"));
+        assert_eq!(
+            siv_decrypt(mac_key, ctr_key, &[&associated_data], &ciphertext, &siv),
+            Ok(plaintext)
+        );
+    }
+
+    // RFC 5297, appendix A.2, nonce-based authenticated encryption
+    #[test]
+    fn test_siv_nonce_based() {
+        let mac_key: [u8; 16] = hex_to_bytes("7f7e7d7c7b7a79787776757473727170")
+            .try_into()
+            .unwrap();
+        let ctr_key: [u8; 16] = hex_to_bytes("404142434445464748494a4b4c4d4e4f")
+            .try_into()
+            .unwrap();
+        let header = hex_to_bytes(
+            "00112233445566778899aabbccddeeffdeaddadadeaddadaffeeddccbbaa9988\
+             7766554433221100",
+        );
+        let other_header = hex_to_bytes("102030405060708090a0");
+        let nonce = hex_to_bytes("09f911029d74e35bd84156c5635688c0");
+        let associated_data: [&[u8]; 3] = [&header, &other_header, &nonce];
+        let plaintext = hex_to_bytes(
+            "7468697320697320736f6d6520706c61696e7465787420746f20656e63727970\
+             74207573696e67205349562d414553",
+        );
+
+        let (ciphertext, siv) =
+            siv_encrypt(mac_key, ctr_key, &associated_data, &plaintext).unwrap();
+        assert_eq!(
+            siv.to_vec(),
+            hex_to_bytes("7bdb6e3b432667eb06f4d14bff2fbd0f")
+        );
+        assert_eq!(
+            ciphertext,
+            hex_to_bytes(
+                "cb900f2fddbe404326601965c889bf17dba77ceb094fa663b7a3f748ba8af829\
+                 ea64ad544a272e9c485b62a3fd5c0d"
+            )
+        );
+        assert_eq!(
+            siv_decrypt(mac_key, ctr_key, &associated_data, &ciphertext, &siv),
+            Ok(plaintext)
+        );
+    }
+
+    // AES-SIV-384 and AES-SIV-512, cross-checked with OpenSSL
+    #[test]
+    fn test_siv_larger_keys() {
+        let key: Vec<u8> = (0..64).collect();
+
+        let mac_key: [u8; 24] = key[..24].try_into().unwrap();
+        let ctr_key: [u8; 24] = key[24..48].try_into().unwrap();
+        let (ciphertext, siv) = siv_encrypt(mac_key, ctr_key, &[], &[]).unwrap();
+        assert!(ciphertext.is_empty());
+        assert_eq!(
+            siv.to_vec(),
+            hex_to_bytes("9e44df35c90f1138e1788e367d580588")
+        );
+
+        let mac_key: [u8; 32] = key[..32].try_into().unwrap();
+        let ctr_key: [u8; 32] = key[32..].try_into().unwrap();
+        let plaintext = b"hello world, siv";
+        let (ciphertext, siv) = siv_encrypt(mac_key, ctr_key, &[b"a", b""], plaintext).unwrap();
+        assert_eq!(
+            siv.to_vec(),
+            hex_to_bytes("f501f08a52e9a56236c6cbc43f128dbd")
+        );
+        assert_eq!(ciphertext, hex_to_bytes("e12e50d94e12264d28c17ec55dcb21cc"));
+        assert_eq!(
+            siv_decrypt(mac_key, ctr_key, &[b"a", b""], &ciphertext, &siv),
+            Ok(plaintext.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_siv_rejects_forgeries() {
+        let mac_key = [0x42u8; 16];
+        let ctr_key = [0x24u8; 16];
+        let (ciphertext, siv) = siv_encrypt(mac_key, ctr_key, &[b"header"], b"member").unwrap();
+
+        // The same inputs always give the same ciphertext
+        assert_eq!(
+            siv_encrypt(mac_key, ctr_key, &[b"header"], b"member"),
+            Ok((ciphertext.clone(), siv))
+        );
+
+        let mut forged_ciphertext = ciphertext.clone();
+        forged_ciphertext[0] ^= 1;
+        assert_eq!(
+            siv_decrypt(mac_key, ctr_key, &[b"header"], &forged_ciphertext, &siv),
+            Err(Error::AuthenticationFailed)
+        );
+        // The order and the boundaries of the components are authenticated
+        assert_eq!(
+            siv_decrypt(mac_key, ctr_key, &[b"head", b"er"], &ciphertext, &siv),
+            Err(Error::AuthenticationFailed)
+        );
+        assert_eq!(
+            siv_decrypt(mac_key, ctr_key, &[], &ciphertext, &siv),
+            Err(Error::AuthenticationFailed)
+        );
+
+        let too_many_components: Vec<&[u8]> = vec![b""; 127];
+        assert_eq!(
+            siv_encrypt(mac_key, ctr_key, &too_many_components, b"member"),
+            Err(Error::InvalidLength)
+        );
+    }
+}
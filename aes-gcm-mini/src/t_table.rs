@@ -0,0 +1,90 @@
+//! AES encryption with T-tables, selected for all modes with the `t-table` feature.
+//!
+//! Each T-table entry combines SubBytes and the MixColumns coefficients of one input byte, so a
+//! round is sixteen table lookups and XORs on `u32` columns instead of the byte-by-byte
+//! `mix_column` and `gfmulx`. In the zkVM a load costs about as much as any other instruction,
+//! which makes this the cheaper round function, but on native targets the secret-indexed
+//! lookups are a cache-timing side channel, so `constant-time` takes precedence over it.
+
+use crate::aes::SBOX;
+use crate::utils::State;
+
+const TE0: [u32; 256] = te0();
+const TE1: [u32; 256] = rotate_table(&TE0, 8);
+const TE2: [u32; 256] = rotate_table(&TE0, 16);
+const TE3: [u32; 256] = rotate_table(&TE0, 24);
+
+// Te0[x] is the column (2 S[x], S[x], S[x], 3 S[x]), most significant byte first
+const fn te0() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let s = SBOX[i];
+        let s2 = (s << 1) ^ (0x1b * (s >> 7));
+        let s3 = s2 ^ s;
+        table[i] = u32::from_be_bytes([s2, s, s, s3]);
+        i += 1;
+    }
+    table
+}
+
+// The other tables are Te0 for the input byte in the lower rows of the column
+const fn rotate_table(table: &[u32; 256], bits: u32) -> [u32; 256] {
+    let mut rotated = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        rotated[i] = table[i].rotate_right(bits);
+        i += 1;
+    }
+    rotated
+}
+
+// Cipher function, the number of rounds follows from the length of the key schedule
+pub(crate) fn cipher(state: State, key_expanded: &[u32]) -> State {
+    let rounds = key_expanded.len() / 4 - 1;
+    let mut state = state;
+    for c in 0..4 {
+        state[c] ^= key_expanded[c];
+    }
+
+    for round in 1..rounds {
+        let mut next: State = [0; 4];
+        for c in 0..4 {
+            next[c] = TE0[(state[c] >> 24) as usize]
+                ^ TE1[((state[(c + 1) % 4] >> 16) & 0xff) as usize]
+                ^ TE2[((state[(c + 2) % 4] >> 8) & 0xff) as usize]
+                ^ TE3[(state[(c + 3) % 4] & 0xff) as usize]
+                ^ key_expanded[round * 4 + c];
+        }
+        state = next;
+    }
+
+    // The last round has no MixColumns, so it substitutes the bytes directly
+    let mut new_state: State = [0; 4];
+    for c in 0..4 {
+        let column = [
+            SBOX[(state[c] >> 24) as usize],
+            SBOX[((state[(c + 1) % 4] >> 16) & 0xff) as usize],
+            SBOX[((state[(c + 2) % 4] >> 8) & 0xff) as usize],
+            SBOX[(state[(c + 3) % 4] & 0xff) as usize],
+        ];
+        new_state[c] = u32::from_be_bytes(column) ^ key_expanded[rounds * 4 + c];
+    }
+    new_state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables() {
+        // FIPS-197 5.1.3, and the first entries of the tables in the Rijndael proposal
+        assert_eq!(TE0[0x00], 0xc66363a5);
+        assert_eq!(TE0[0x01], 0xf87c7c84);
+        assert_eq!(TE1[0x00], 0xa5c66363);
+        assert_eq!(TE2[0x00], 0x63a5c663);
+        assert_eq!(TE3[0x00], 0x6363a5c6);
+        assert_eq!(TE0[0xff], 0x2c16163a);
+    }
+}
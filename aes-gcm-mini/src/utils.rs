@@ -1,5 +1,7 @@
 pub type Stream = [u8; 16];
 pub type Block = [[u8; 4]; 4];
+// Internal AES state, one word per column with the first row in the most significant byte
+pub(crate) type State = [u32; 4];
 
 pub(crate) fn stream_xor(stream1: Stream, stream2: Stream) -> Stream {
     let mut result: Stream = [0; 16];
@@ -9,15 +11,8 @@ pub(crate) fn stream_xor(stream1: Stream, stream2: Stream) -> Stream {
     result
 }
 
-pub(crate) fn word_xor(word1: [u8; 4], word2: [u8; 4]) -> [u8; 4] {
-    let mut result: [u8; 4] = [0; 4];
-    for i in 0..4 {
-        result[i] = word1[i] ^ word2[i];
-    }
-    result
-}
-
-pub(crate) fn stream_to_block(stream: Stream) -> Block {
+/// Arranges 16 bytes into the row-major block taken by [`aes`](crate::aes), column by column.
+pub fn stream_to_block(stream: Stream) -> Block {
     let mut block: Block = [[0; 4]; 4];
     for i in 0..4 {
         for j in 0..4 {
@@ -27,7 +22,8 @@ pub(crate) fn stream_to_block(stream: Stream) -> Block {
     block
 }
 
-pub(crate) fn block_to_stream(block: Block) -> Stream {
+/// Reads a block back into bytes, the inverse of [`stream_to_block`].
+pub fn block_to_stream(block: Block) -> Stream {
     let mut stream: Stream = [0; 16];
     for i in 0..4 {
         for j in 0..4 {
@@ -37,6 +33,121 @@ pub(crate) fn block_to_stream(block: Block) -> Stream {
     stream
 }
 
+pub(crate) fn stream_to_state(stream: Stream) -> State {
+    let mut state: State = [0; 4];
+    for i in 0..4 {
+        state[i] = u32::from_be_bytes([
+            stream[i * 4],
+            stream[i * 4 + 1],
+            stream[i * 4 + 2],
+            stream[i * 4 + 3],
+        ]);
+    }
+    state
+}
+
+pub(crate) fn state_to_stream(state: State) -> Stream {
+    let mut stream: Stream = [0; 16];
+    for i in 0..4 {
+        stream[i * 4..i * 4 + 4].copy_from_slice(&state[i].to_be_bytes());
+    }
+    stream
+}
+
+pub(crate) fn block_to_state(block: Block) -> State {
+    let mut state: State = [0; 4];
+    for i in 0..4 {
+        state[i] = u32::from_be_bytes([block[0][i], block[1][i], block[2][i], block[3][i]]);
+    }
+    state
+}
+
+pub(crate) fn state_to_block(state: State) -> Block {
+    let mut block: Block = [[0; 4]; 4];
+    for i in 0..4 {
+        let column = state[i].to_be_bytes();
+        for j in 0..4 {
+            block[j][i] = column[j];
+        }
+    }
+    block
+}
+
+// Splits bytes into blocks, zero padding the last one
+#[cfg(feature = "alloc")]
+pub(crate) fn pad_to_streams(bytes: &[u8]) -> alloc::vec::Vec<Stream> {
+    let mut streams = alloc::vec::Vec::with_capacity(bytes.len().div_ceil(16));
+    for chunk in bytes.chunks(16) {
+        let mut stream: Stream = [0; 16];
+        stream[..chunk.len()].copy_from_slice(chunk);
+        streams.push(stream);
+    }
+    streams
+}
+
+// Compares two byte strings without branching on their contents, to not leak where they differ
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0;
+    for i in 0..a.len() {
+        diff |= a[i] ^ b[i];
+    }
+    diff == 0
+}
+
+// Overwrites secret temporaries with zeros before they go out of scope, with volatile writes
+// that the compiler cannot elide. Without the `zeroize` feature this does nothing.
+#[cfg(feature = "zeroize")]
+pub(crate) fn wipe<Z: zeroize::Zeroize + ?Sized>(secret: &mut Z) {
+    secret.zeroize();
+}
+
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn wipe<Z: ?Sized>(_secret: &mut Z) {}
+
+#[cfg(test)]
+pub(crate) fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    let hex: String = hex.split_whitespace().collect();
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// Deterministic xorshift generator, good enough for test inputs
+#[cfg(test)]
+pub(crate) struct Rng(pub(crate) u64);
+
+#[cfg(test)]
+impl Rng {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub(crate) fn stream(&mut self) -> Stream {
+        let mut stream = [0; 16];
+        stream[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+        stream[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+        stream
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_pad_to_streams() {
+    assert!(pad_to_streams(&[]).is_empty());
+    assert_eq!(pad_to_streams(&[0x01; 16]), vec![[0x01; 16]]);
+
+    let mut expected: Stream = [0x00; 16];
+    expected[0] = 0x01;
+    assert_eq!(pad_to_streams(&[0x01; 17]), vec![[0x01; 16], expected]);
+}
+
 #[test]
 fn test_stream_to_block() {
     let stream: Stream = [0x00; 16];
@@ -60,9 +171,7 @@ fn test_stream_to_block() {
 
 pub(crate) fn bitwise_right_shift(bits: [u8; 128]) -> [u8; 128] {
     let mut result: [u8; 128] = [0; 128];
-    for i in 1..128 {
-        result[i] = bits[i - 1];
-    }
+    result[1..].copy_from_slice(&bits[..127]);
     result
 }
 
@@ -104,12 +213,38 @@ pub(crate) fn byte_to_bits(byte: u8) -> [u8; 8] {
 
 pub(crate) fn bits_to_byte(bits: [u8; 8]) -> u8 {
     let mut byte = 0;
-    for j in 0..8 {
-        byte |= bits[j] << (7 - j);
+    for (j, bit) in bits.iter().enumerate() {
+        byte |= bit << (7 - j);
     }
     byte
 }
 
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_wipe() {
+    let mut key: Stream = [0xaa; 16];
+    wipe(&mut key);
+    assert_eq!(key, [0; 16]);
+
+    let mut key_expanded = [u32::MAX; 44];
+    wipe(&mut key_expanded[4..]);
+    assert_eq!(key_expanded[..4], [u32::MAX; 4]);
+    assert_eq!(key_expanded[4..], [0; 40]);
+}
+
+#[test]
+fn test_stream_to_state() {
+    let stream: Stream = [
+        0x32, 0x88, 0x31, 0xe0, 0x43, 0x5a, 0x31, 0x37, 0xf6, 0x30, 0x98, 0x07, 0xa8, 0x8d, 0xa2,
+        0x34,
+    ];
+    let state: State = [0x328831e0, 0x435a3137, 0xf6309807, 0xa88da234];
+    assert_eq!(stream_to_state(stream), state);
+    assert_eq!(state_to_stream(state), stream);
+    assert_eq!(block_to_state(stream_to_block(stream)), state);
+    assert_eq!(state_to_block(state), stream_to_block(stream));
+}
+
 #[test]
 fn test_bitwise_right_shift() {
     let input1: [u8; 128] = [
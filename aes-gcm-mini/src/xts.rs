@@ -0,0 +1,365 @@
+use crate::aes::AesKey;
+use crate::error::Error;
+use crate::utils::{stream_xor, Stream};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Keys of XTS-AES, which IEEE 1619 only defines with 128 and 256-bit keys.
+pub trait XtsKey: AesKey {}
+
+impl XtsKey for [u8; 16] {}
+
+impl XtsKey for [u8; 32] {}
+
+// Data units are at least one block and at most 2^20 blocks long (IEEE 1619, section 5.1)
+const MAX_DATA_UNIT_LEN: usize = 16 << 20;
+
+// XTS-AES encryption of a data unit, e.g. a disk sector, with its sequence number. A trailing
+// partial block is handled with ciphertext stealing, so the ciphertext is as long as the
+// plaintext.
+#[cfg(feature = "alloc")]
+pub fn xts_encrypt<K: XtsKey>(
+    data_key: K,
+    tweak_key: K,
+    data_unit: u128,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut ciphertext = plaintext.to_vec();
+    xts_encrypt_in_place(data_key, tweak_key, data_unit, &mut ciphertext)?;
+    Ok(ciphertext)
+}
+
+// XTS-AES decryption of a data unit with its sequence number
+#[cfg(feature = "alloc")]
+pub fn xts_decrypt<K: XtsKey>(
+    data_key: K,
+    tweak_key: K,
+    data_unit: u128,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut plaintext = ciphertext.to_vec();
+    xts_decrypt_in_place(data_key, tweak_key, data_unit, &mut plaintext)?;
+    Ok(plaintext)
+}
+
+// XTS-AES encryption of a data unit in place
+pub fn xts_encrypt_in_place<K: XtsKey>(
+    data_key: K,
+    tweak_key: K,
+    data_unit: u128,
+    buffer: &mut [u8],
+) -> Result<(), Error> {
+    check_length(buffer)?;
+    let full_blocks = buffer.len() / 16;
+    let partial_len = buffer.len() % 16;
+
+    let mut tweak = initial_tweak(tweak_key, data_unit);
+    for i in 0..full_blocks {
+        let block = encrypt_block(data_key, tweak, &buffer[i * 16..(i + 1) * 16]);
+        buffer[i * 16..(i + 1) * 16].copy_from_slice(&block);
+        tweak = mul_alpha(tweak);
+    }
+
+    if partial_len > 0 {
+        // The partial block steals the tail of the previous ciphertext block, which then
+        // takes its place, and the head of that block becomes the partial ciphertext block
+        let last_full_start = (full_blocks - 1) * 16;
+        let mut stolen: Stream = [0; 16];
+        stolen[..partial_len].copy_from_slice(&buffer[full_blocks * 16..]);
+        stolen[partial_len..]
+            .copy_from_slice(&buffer[last_full_start + partial_len..][..16 - partial_len]);
+        let mut head: Stream = [0; 16];
+        head[..partial_len]
+            .copy_from_slice(&buffer[last_full_start..last_full_start + partial_len]);
+        let block = encrypt_block(data_key, tweak, &stolen);
+        buffer[last_full_start..last_full_start + 16].copy_from_slice(&block);
+        buffer[full_blocks * 16..].copy_from_slice(&head[..partial_len]);
+    }
+
+    Ok(())
+}
+
+// XTS-AES decryption of a data unit in place
+pub fn xts_decrypt_in_place<K: XtsKey>(
+    data_key: K,
+    tweak_key: K,
+    data_unit: u128,
+    buffer: &mut [u8],
+) -> Result<(), Error> {
+    check_length(buffer)?;
+    let partial_len = buffer.len() % 16;
+    // With ciphertext stealing the last full block is decrypted together with the partial one
+    let full_blocks = if partial_len > 0 {
+        buffer.len() / 16 - 1
+    } else {
+        buffer.len() / 16
+    };
+
+    let mut tweak = initial_tweak(tweak_key, data_unit);
+    for i in 0..full_blocks {
+        let block = decrypt_block(data_key, tweak, &buffer[i * 16..(i + 1) * 16]);
+        buffer[i * 16..(i + 1) * 16].copy_from_slice(&block);
+        tweak = mul_alpha(tweak);
+    }
+
+    if partial_len > 0 {
+        // The last full ciphertext block was encrypted with the tweak of the partial block
+        let last_full_start = full_blocks * 16;
+        let stolen = decrypt_block(
+            data_key,
+            mul_alpha(tweak),
+            &buffer[last_full_start..last_full_start + 16],
+        );
+        let mut last_full: Stream = [0; 16];
+        last_full[..partial_len].copy_from_slice(&buffer[last_full_start + 16..]);
+        last_full[partial_len..].copy_from_slice(&stolen[partial_len..]);
+        let block = decrypt_block(data_key, tweak, &last_full);
+        buffer[last_full_start..last_full_start + 16].copy_from_slice(&block);
+        buffer[last_full_start + 16..].copy_from_slice(&stolen[..partial_len]);
+    }
+
+    Ok(())
+}
+
+fn check_length(data: &[u8]) -> Result<(), Error> {
+    if data.len() < 16 || data.len() > MAX_DATA_UNIT_LEN {
+        return Err(Error::InvalidLength);
+    }
+    Ok(())
+}
+
+// The tweak of the first block is the encryption of the little endian data unit number
+fn initial_tweak<K: AesKey>(tweak_key: K, data_unit: u128) -> Stream {
+    tweak_key.encrypt_stream(data_unit.to_le_bytes())
+}
+
+// C = E(P xor T) xor T
+fn encrypt_block<K: AesKey>(key: K, tweak: Stream, input: &[u8]) -> Stream {
+    let mut block: Stream = [0; 16];
+    block.copy_from_slice(input);
+    stream_xor(key.encrypt_stream(stream_xor(block, tweak)), tweak)
+}
+
+// P = D(C xor T) xor T
+fn decrypt_block<K: AesKey>(key: K, tweak: Stream, input: &[u8]) -> Stream {
+    let mut block: Stream = [0; 16];
+    block.copy_from_slice(input);
+    stream_xor(key.decrypt_stream(stream_xor(block, tweak)), tweak)
+}
+
+/// Multiplication of the binary extension field by alpha (x), with the XTS byte order
+/// the block is a little endian integer that is left shifted by one bit
+/// if the msb of the last byte is one, then the first byte is XORed with 0x87
+/// this 0x87 is the hex representation of 10000111, which represents
+/// the polynomial x^7 + x^2 + x + 1
+fn mul_alpha(tweak: Stream) -> Stream {
+    let mut result: Stream = [0; 16];
+    let mut carry = 0;
+    for i in 0..16 {
+        result[i] = (tweak[i] << 1) | carry;
+        carry = tweak[i] >> 7;
+    }
+    if carry == 1 {
+        result[0] ^= 0x87;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex_to_bytes;
+
+    #[cfg(feature = "alloc")]
+    fn check_vector<K: XtsKey>(
+        data_key: K,
+        tweak_key: K,
+        data_unit: u128,
+        plaintext: &[u8],
+        ciphertext: &str,
+    ) {
+        let ciphertext = hex_to_bytes(ciphertext);
+        assert_eq!(
+            xts_encrypt(data_key, tweak_key, data_unit, plaintext),
+            Ok(ciphertext.clone())
+        );
+        assert_eq!(
+            xts_decrypt(data_key, tweak_key, data_unit, &ciphertext),
+            Ok(plaintext.to_vec())
+        );
+    }
+
+    // IEEE 1619, Annex B, vectors 1 to 3
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_xts_aes_128() {
+        check_vector(
+            [0x00u8; 16],
+            [0x00; 16],
+            0,
+            &[0x00; 32],
+            "917cf69ebd68b2ec9b9fe9a3eadda692cd43d2f59598ed858c02c2652fbf922e",
+        );
+        check_vector(
+            [0x11u8; 16],
+            [0x22; 16],
+            0x3333333333,
+            &[0x44; 32],
+            "c454185e6a16936e39334038acef838bfb186fff7480adc4289382ecd6d394f0",
+        );
+        let data_key: [u8; 16] = hex_to_bytes("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0")
+            .try_into()
+            .unwrap();
+        check_vector(
+            data_key,
+            [0x22; 16],
+            0x3333333333,
+            &[0x44; 32],
+            "af85336b597afc1a900b2eb21ec949d292df4c047e0b21532186a5971a227a89",
+        );
+    }
+
+    // IEEE 1619, Annex B, vector 10
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_xts_aes_256() {
+        let data_key: [u8; 32] =
+            hex_to_bytes("2718281828459045235360287471352662497757247093699959574966967627")
+                .try_into()
+                .unwrap();
+        let tweak_key: [u8; 32] =
+            hex_to_bytes("3141592653589793238462643383279502884197169399375105820974944592")
+                .try_into()
+                .unwrap();
+        let plaintext: Vec<u8> = (0..512).map(|i| i as u8).collect();
+        check_vector(
+            data_key,
+            tweak_key,
+            0xff,
+            &plaintext,
+            "1c3b3a102f770386e4836c99e370cf9bea00803f5e482357a4ae12d414a3e63b\
+             5d31e276f8fe4a8d66b317f9ac683f44680a86ac35adfc3345befecb4bb188fd\
+             5776926c49a3095eb108fd1098baec70aaa66999a72a82f27d848b21d4a741b0\
+             c5cd4d5fff9dac89aeba122961d03a757123e9870f8acf1000020887891429ca\
+             2a3e7a7d7df7b10355165c8b9a6d0a7de8b062c4500dc4cd120c0f7418dae3d0\
+             b5781c34803fa75421c790dfe1de1834f280d7667b327f6c8cd7557e12ac3a0f\
+             93ec05c52e0493ef31a12d3d9260f79a289d6a379bc70c50841473d1a8cc81ec\
+             583e9645e07b8d9670655ba5bbcfecc6dc3966380ad8fecb17b6ba02469a020a\
+             84e18e8f84252070c13e9f1f289be54fbc481457778f616015e1327a02b140f1\
+             505eb309326d68378f8374595c849d84f4c333ec4423885143cb47bd71c5edae\
+             9be69a2ffeceb1bec9de244fbe15992b11b77c040f12bd8f6a975a44a0f90c29\
+             a9abc3d4d893927284c58754cce294529f8614dcd2aba991925fedc4ae74ffac\
+             6e333b93eb4aff0479da9a410e4450e0dd7ae4c6e2910900575da401fc07059f\
+             645e8b7e9bfdef33943054ff84011493c27b3429eaedb4ed5376441a77ed4385\
+             1ad77f16f541dfd269d50d6a5f14fb0aab1cbb4c1550be97f7ab4066193c4caa\
+             773dad38014bd2092fa755c824bb5e54c4f36ffda9fcea70b9c6e693e148c151",
+        );
+    }
+
+    // IEEE 1619, Annex B, vectors 15 to 18, which steal ciphertext for 1 to 4 bytes
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_xts_ciphertext_stealing() {
+        let data_key: [u8; 16] = hex_to_bytes("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0")
+            .try_into()
+            .unwrap();
+        let tweak_key: [u8; 16] = hex_to_bytes("bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0")
+            .try_into()
+            .unwrap();
+        let plaintext: Vec<u8> = (0..20).collect();
+        let ciphertexts = [
+            "6c1625db4671522d3d7599601de7ca09ed",
+            "d069444b7a7e0cab09e24447d24deb1fedbf",
+            "e5df1351c0544ba1350b3363cd8ef4beedbf9d",
+            "9d84c813f719aa2c7be3f66171c7c5c2edbf9dac",
+        ];
+        for (len, ciphertext) in (17..=20).zip(ciphertexts) {
+            check_vector(
+                data_key,
+                tweak_key,
+                0x123456789a,
+                &plaintext[..len],
+                ciphertext,
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_xts_roundtrip() {
+        let data_key = [0x42u8; 32];
+        let tweak_key = [0x24u8; 32];
+        for len in [16, 31, 32, 33, 47, 512, 4096] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let ciphertext = xts_encrypt(data_key, tweak_key, 7, &plaintext).unwrap();
+            assert_eq!(ciphertext.len(), len);
+            assert_ne!(
+                xts_encrypt(data_key, tweak_key, 8, &plaintext),
+                Ok(ciphertext.clone())
+            );
+            assert_eq!(
+                xts_decrypt(data_key, tweak_key, 7, &ciphertext),
+                Ok(plaintext)
+            );
+        }
+        assert_eq!(
+            xts_encrypt(data_key, tweak_key, 0, &[0; 15]),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(
+            xts_decrypt(data_key, tweak_key, 0, &[0; 15]),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    // IEEE 1619, Annex B, vector 18
+    #[test]
+    fn test_xts_in_place() {
+        let data_key: [u8; 16] = hex_to_bytes("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0")
+            .try_into()
+            .unwrap();
+        let tweak_key: [u8; 16] = hex_to_bytes("bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0")
+            .try_into()
+            .unwrap();
+        let mut buffer: [u8; 20] = core::array::from_fn(|i| i as u8);
+        assert_eq!(
+            xts_encrypt_in_place(data_key, tweak_key, 0x123456789a, &mut buffer),
+            Ok(())
+        );
+        assert_eq!(
+            buffer.to_vec(),
+            hex_to_bytes("9d84c813f719aa2c7be3f66171c7c5c2edbf9dac")
+        );
+        assert_eq!(
+            xts_decrypt_in_place(data_key, tweak_key, 0x123456789a, &mut buffer),
+            Ok(())
+        );
+        assert_eq!(buffer, core::array::from_fn(|i| i as u8));
+        assert_eq!(
+            xts_encrypt_in_place(data_key, tweak_key, 0, &mut [0; 15]),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_mul_alpha() {
+        let mut tweak: Stream = [0; 16];
+        tweak[0] = 0x01;
+        let mut expected: Stream = [0; 16];
+        expected[0] = 0x02;
+        assert_eq!(mul_alpha(tweak), expected);
+
+        // The carry propagates from the first byte to the last one
+        let mut tweak: Stream = [0; 16];
+        tweak[0] = 0x80;
+        let mut expected: Stream = [0; 16];
+        expected[1] = 0x01;
+        assert_eq!(mul_alpha(tweak), expected);
+
+        let mut tweak: Stream = [0; 16];
+        tweak[15] = 0x80;
+        let mut expected: Stream = [0; 16];
+        expected[0] = 0x87;
+        assert_eq!(mul_alpha(tweak), expected);
+    }
+}
@@ -1,11 +1,19 @@
+mod sweep;
+
 use methods::{GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID};
-use prettytable::{row, Table};
+use prettytable::{row, Cell, Table};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use risc0_zkp::hal::tracker;
 use risc0_zkvm::serde::from_slice;
-use risc0_zkvm::{default_prover, ExecutorEnv};
+use risc0_zkvm::{default_prover, ExecutorEnv, ExecutorImpl, NullSegmentRef, ProverOpts};
 use serde::Serialize;
 use serde_with::{serde_as, DurationNanoSeconds};
-use shared::{AesGcmNativeTestCase, AesGcmTestCase, AesTestCase, TestCase};
+use shared::{
+    AesCcmTestCase, AesGcmNativeTestCase, AesGcmTestCase, AesGcmVecNativeTestCase,
+    AesGcmVecTestCase, AesTestCase, CmacTestCase, GmacTestCase, GuestOutput, KeyUnwrapTestCase,
+    TestCase,
+};
 use std::time::{Duration, Instant};
 
 #[serde_as]
@@ -15,9 +23,86 @@ struct PerformanceData {
     #[serde_as(as = "DurationNanoSeconds")]
     duration: Duration,
     cycles: u64,
+    user_cycles: u64,
     ram: usize,
     seal: usize,
     throughput: f32,
+    #[serde_as(as = "Option<DurationNanoSeconds>")]
+    compression: Option<Duration>,
+    succinct_seal: Option<usize>,
+    profile: Vec<(String, u64)>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+struct ExecutionData {
+    name: String,
+    #[serde_as(as = "DurationNanoSeconds")]
+    duration: Duration,
+    total_cycles: u64,
+    user_cycles: u64,
+    paging_cycles: u64,
+    segments: usize,
+    profile: Vec<(String, u64)>,
+}
+
+/// Command line options of the benchmark harness.
+#[derive(Debug)]
+struct Options {
+    /// Only execute the guest and report cycle counts, skipping proof generation.
+    execute_only: bool,
+    /// Sweep AES-GCM over message and key sizes instead of running the default test cases.
+    sweep: bool,
+    /// Compress the composite receipt into a succinct receipt after proving.
+    succinct: bool,
+    /// Plaintext size, in bytes, of the multi-block AES-GCM test cases.
+    plaintext_len: usize,
+    /// AAD size, in bytes, of the multi-block AES-GCM test cases.
+    aad_len: usize,
+    /// Seed of the RNG generating the inputs of the multi-block AES-GCM test cases.
+    seed: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            execute_only: false,
+            sweep: false,
+            succinct: false,
+            plaintext_len: 256,
+            aad_len: 32,
+            seed: 0,
+        }
+    }
+}
+
+impl Options {
+    fn from_args() -> Self {
+        let mut options = Self::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--execute" => options.execute_only = true,
+                "--sweep" => options.sweep = true,
+                "--succinct" => options.succinct = true,
+                "--size" => options.plaintext_len = parse_value(&arg, args.next()),
+                "--aad-size" => options.aad_len = parse_value(&arg, args.next()),
+                "--seed" => options.seed = parse_value(&arg, args.next()),
+                _ => panic!("Unknown argument: {}", arg),
+            }
+        }
+        // Only a proof produces a receipt that can be compressed
+        if options.execute_only && options.succinct {
+            panic!("--succinct cannot be combined with --execute");
+        }
+        options
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: Option<String>) -> T {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| panic!("{} expects a non-negative integer", arg))
 }
 
 fn main() {
@@ -25,8 +110,22 @@ fn main() {
         .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
         .init();
 
+    let options = Options::from_args();
+
+    if options.sweep {
+        sweep::run_sweep(&options);
+        return;
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    let vec_test_case = random_gcm_test_case(&mut rng, 16, options.aad_len, options.plaintext_len);
+
     let test_cases = vec![
         TestCase("AES".to_string(), AesTestCase::default_case().to_bytes()),
+        TestCase(
+            "AES-T-table".to_string(),
+            AesTestCase::default_case().to_bytes(),
+        ),
         TestCase(
             "AES-GCM".to_string(),
             AesGcmTestCase::default_case().to_bytes(),
@@ -35,71 +134,299 @@ fn main() {
             "AES-GCM-native".to_string(),
             AesGcmNativeTestCase::default_case().to_bytes(),
         ),
+        TestCase("AES-GCM-vec".to_string(), vec_test_case.to_bytes()),
+        TestCase(
+            "AES-GCM-vec-native".to_string(),
+            AesGcmVecNativeTestCase(vec_test_case).to_bytes(),
+        ),
+        TestCase(
+            "AES-CCM".to_string(),
+            AesCcmTestCase::default_case().to_bytes(),
+        ),
+        TestCase("CMAC".to_string(), CmacTestCase::default_case().to_bytes()),
+        TestCase("GMAC".to_string(), GmacTestCase::default_case().to_bytes()),
+        TestCase(
+            "AES-KW-unwrap".to_string(),
+            KeyUnwrapTestCase::default_case().to_bytes(),
+        ),
     ];
 
+    if options.execute_only {
+        execute_test_cases(test_cases);
+    } else {
+        prove_test_cases(test_cases, &options);
+    }
+}
+
+/// Generates an AES-GCM test case over random inputs, with the expected output computed by
+/// RustCrypto, and checks that aes-gcm-mini agrees with it before the case is proven.
+fn random_gcm_test_case(
+    rng: &mut ChaCha8Rng,
+    key_len: usize,
+    aad_len: usize,
+    plaintext_len: usize,
+) -> AesGcmVecTestCase {
+    let mut key = vec![0; key_len];
+    let mut iv = [0; 12];
+    let mut aad = vec![0; aad_len];
+    let mut plaintext = vec![0; plaintext_len];
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut iv);
+    rng.fill_bytes(&mut aad);
+    rng.fill_bytes(&mut plaintext);
+
+    let test_case = AesGcmVecTestCase::new(key, iv, aad, plaintext);
+    assert!(
+        test_case.is_valid(),
+        "aes-gcm-mini disagrees with RustCrypto for a {}-bit key, {} B AAD and {} B plaintext",
+        key_len * 8,
+        aad_len,
+        plaintext_len
+    );
+    test_case
+}
+
+fn prove_test_cases(test_cases: Vec<TestCase>, options: &Options) {
     let mut table = Table::new();
-    table.add_row(row![
+    let mut header = row![
         "Name",
         "Duration (ms)",
         "Cycles",
         "RAM (bytes)",
         "Seal (bytes)",
         "Throughput (Hz)"
-    ]);
+    ];
+    if options.succinct {
+        header.add_cell(Cell::new("Compression (ms)"));
+        header.add_cell(Cell::new("Succinct Seal (bytes)"));
+    }
+    table.add_row(header);
 
+    let mut profiles = vec![];
+    let mut user_cycles = vec![];
     for test_case in test_cases {
-        let serialized_test_case = test_case.to_bytes();
+        let performance_data = prove(&test_case, options);
+        user_cycles.push((performance_data.name.clone(), performance_data.user_cycles));
 
-        let env = ExecutorEnv::builder()
-            .write(&serialized_test_case)
-            .unwrap()
-            .build()
-            .unwrap();
+        let mut row = row![
+            performance_data.name,
+            format!("{:.2}", performance_data.duration.as_millis()),
+            performance_data.cycles,
+            performance_data.ram,
+            performance_data.seal,
+            format!("{:.2}", performance_data.throughput)
+        ];
+        if let (Some(compression), Some(succinct_seal)) =
+            (performance_data.compression, performance_data.succinct_seal)
+        {
+            row.add_cell(Cell::new(&compression.as_millis().to_string()));
+            row.add_cell(Cell::new(&succinct_seal.to_string()));
+        }
+        table.add_row(row);
+        profiles.push((performance_data.name, performance_data.profile));
+    }
 
-        let prover = default_prover();
-        tracker().lock().unwrap().reset();
-        let start = Instant::now();
-        let prove_info = prover.prove(env, GUEST_CODE_FOR_ZK_PROOF_ELF).unwrap();
-        let duration = start.elapsed();
+    println!("### Performance Data");
+    table.printstd();
+    print_t_table_comparison(&user_cycles);
+    print_cycle_breakdown(&profiles);
+}
+
+fn prove(test_case: &TestCase, options: &Options) -> PerformanceData {
+    let serialized_test_case = test_case.to_bytes();
+
+    let env = ExecutorEnv::builder()
+        .write(&serialized_test_case)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let prover = default_prover();
+    tracker().lock().unwrap().reset();
+    let start = Instant::now();
+    let prove_info = prover.prove(env, GUEST_CODE_FOR_ZK_PROOF_ELF).unwrap();
+    let duration = start.elapsed();
 
-        let receipt = prove_info.receipt;
-        receipt.verify(GUEST_CODE_FOR_ZK_PROOF_ID).unwrap();
+    let receipt = prove_info.receipt;
+    receipt.verify(GUEST_CODE_FOR_ZK_PROOF_ID).unwrap();
 
-        let journal = receipt.journal;
-        let result: bool = from_slice(&journal.bytes).unwrap();
-        println!("Result from proof journal: {}", result);
+    let output: GuestOutput = from_slice(&receipt.journal.bytes).unwrap();
+    println!("Result from proof journal: {}", output.is_valid);
 
-        let tracker_lock = tracker().lock().unwrap();
-        let ram_usage = tracker_lock.peak; // TODO: For some reason RAM is 0, fix
-        let cycles = prove_info.stats.total_cycles;
-        let seal_size = receipt
+    // The tracker only sees HAL buffers allocated in this process, which is why
+    // risc0-zkvm is built with the `prove` feature (local, in-process prover).
+    let ram_usage = tracker().lock().unwrap().peak as usize;
+    let cycles = prove_info.stats.total_cycles;
+    let seal_size = receipt
+        .inner
+        .composite()
+        .expect("Receipt is not composite, are you in development mode?")
+        .segments
+        .iter()
+        .map(|x| x.get_seal_bytes().len())
+        .sum();
+    let throughput = cycles as f32 / duration.as_secs_f32();
+
+    let (compression, succinct_seal) = if options.succinct {
+        let start = Instant::now();
+        let succinct_receipt = prover.compress(&ProverOpts::succinct(), &receipt).unwrap();
+        let compression = start.elapsed();
+        succinct_receipt.verify(GUEST_CODE_FOR_ZK_PROOF_ID).unwrap();
+
+        let succinct_seal = succinct_receipt
             .inner
-            .composite()
-            .expect("Receipt is not composite, are you in development mode?")
-            .segments
-            .iter()
-            .map(|x| x.get_seal_bytes().len())
-            .sum();
-        let throughput = cycles as f32 / duration.as_secs_f32();
-        let performance_data = PerformanceData {
-            name: test_case.0,
-            duration,
-            cycles,
-            ram: ram_usage,
-            seal: seal_size,
-            throughput,
-        };
+            .succinct()
+            .expect("Receipt is not succinct, are you in development mode?")
+            .get_seal_bytes()
+            .len();
+        (Some(compression), Some(succinct_seal))
+    } else {
+        (None, None)
+    };
+
+    PerformanceData {
+        name: test_case.0.clone(),
+        duration,
+        cycles,
+        user_cycles: prove_info.stats.user_cycles,
+        ram: ram_usage,
+        seal: seal_size,
+        throughput,
+        compression,
+        succinct_seal,
+        profile: output.profile,
+    }
+}
+
+fn execute_test_cases(test_cases: Vec<TestCase>) {
+    let mut table = Table::new();
+    table.add_row(row![
+        "Name",
+        "Duration (ms)",
+        "Total Cycles",
+        "User Cycles",
+        "Paging Cycles",
+        "Segments"
+    ]);
+
+    let mut profiles = vec![];
+    let mut user_cycles = vec![];
+    for test_case in test_cases {
+        let execution_data = execute(&test_case);
+        user_cycles.push((execution_data.name.clone(), execution_data.user_cycles));
 
         table.add_row(row![
-            performance_data.name,
-            format!("{:.2}", performance_data.duration.as_millis()),
-            performance_data.cycles,
-            performance_data.ram,
-            performance_data.seal,
-            format!("{:.2}", performance_data.throughput)
+            execution_data.name,
+            format!("{:.2}", execution_data.duration.as_millis()),
+            execution_data.total_cycles,
+            execution_data.user_cycles,
+            execution_data.paging_cycles,
+            execution_data.segments
         ]);
+        profiles.push((execution_data.name, execution_data.profile));
     }
 
-    println!("### Performance Data");
+    println!("### Execution Data");
+    table.printstd();
+    print_t_table_comparison(&user_cycles);
+    print_cycle_breakdown(&profiles);
+}
+
+fn execute(test_case: &TestCase) -> ExecutionData {
+    let serialized_test_case = test_case.to_bytes();
+
+    let env = ExecutorEnv::builder()
+        .write(&serialized_test_case)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // `default_executor().execute` only reports per-segment user cycles, so we drive the
+    // executor directly to get the full session statistics, discarding the segments.
+    let start = Instant::now();
+    let session = ExecutorImpl::from_elf(env, GUEST_CODE_FOR_ZK_PROOF_ELF)
+        .unwrap()
+        .run_with_callback(|_| Ok(Box::new(NullSegmentRef)))
+        .unwrap();
+    let duration = start.elapsed();
+
+    let journal = session
+        .journal
+        .as_ref()
+        .expect("Guest did not commit a result");
+    let output: GuestOutput = from_slice(&journal.bytes).unwrap();
+    println!("Result from execution journal: {}", output.is_valid);
+
+    let stats = session.stats();
+    ExecutionData {
+        name: test_case.0.clone(),
+        duration,
+        total_cycles: stats.total_cycles,
+        user_cycles: stats.user_cycles,
+        paging_cycles: stats.paging_cycles,
+        segments: stats.segments,
+        profile: output.profile,
+    }
+}
+
+/// Compares the user cycles of the T-table AES backend with those of the default backend.
+fn print_t_table_comparison(user_cycles: &[(String, u64)]) {
+    let find = |name: &str| {
+        user_cycles
+            .iter()
+            .find(|(case, _)| case == name)
+            .map(|(_, cycles)| *cycles)
+    };
+    let (Some(default), Some(t_table)) = (find("AES"), find("AES-T-table")) else {
+        return;
+    };
+
+    let backend = if cfg!(feature = "t-table") {
+        "T-table"
+    } else {
+        "byte-wise"
+    };
+    println!(
+        "AES-T-table runs in {} user cycles against {} for AES with the {} backend ({:.1}%)",
+        t_table,
+        default,
+        backend,
+        t_table as f64 / default as f64 * 100.0
+    );
+}
+
+/// Renders the per-primitive cycle counts reported by a guest built with the `profile` feature.
+fn print_cycle_breakdown(profiles: &[(String, Vec<(String, u64)>)]) {
+    let Some((_, first)) = profiles.iter().find(|(_, profile)| !profile.is_empty()) else {
+        return;
+    };
+
+    let mut table = Table::new();
+    let mut header = row!["Name"];
+    for (phase, _) in first {
+        header.add_cell(Cell::new(phase));
+    }
+    header.add_cell(Cell::new("Total"));
+    table.add_row(header);
+
+    for (name, profile) in profiles {
+        let mut row = row![name];
+        for (_, cycles) in profile {
+            row.add_cell(Cell::new(&cycles.to_string()));
+        }
+        let total: u64 = profile.iter().map(|(_, cycles)| cycles).sum();
+        row.add_cell(Cell::new(&total.to_string()));
+        table.add_row(row);
+    }
+
+    let ghash_backend = if cfg!(feature = "karatsuba") {
+        "Karatsuba"
+    } else {
+        "bitwise"
+    };
+    println!(
+        "### Cycle Breakdown ({} GHASH multiplication)",
+        ghash_backend
+    );
     table.printstd();
 }
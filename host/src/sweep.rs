@@ -0,0 +1,143 @@
+use crate::{execute, print_cycle_breakdown, prove, random_gcm_test_case, Options};
+use prettytable::{row, Cell, Table};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use shared::{AesGcmVecNativeTestCase, TestCase};
+
+/// Message sizes, in bytes, swept for both the plaintext and the AAD.
+const MESSAGE_SIZES: [usize; 7] = [0, 16, 64, 256, 1024, 4096, 16384];
+
+/// AES key sizes, in bytes.
+const KEY_SIZES: [usize; 3] = [16, 24, 32];
+
+/// A single configuration of the sweep.
+struct SweepCase {
+    key_len: usize,
+    aad_len: usize,
+    plaintext_len: usize,
+    test_case: TestCase,
+}
+
+/// Sweeps the plaintext size with an empty AAD and the AAD size with an empty plaintext, for
+/// every key size and for both the mini and the RustCrypto implementation.
+fn sweep_cases(seed: u64) -> Vec<SweepCase> {
+    let mut sizes: Vec<(usize, usize)> = MESSAGE_SIZES.iter().map(|&len| (0, len)).collect();
+    sizes.extend(
+        MESSAGE_SIZES
+            .iter()
+            .filter(|&&len| len > 0)
+            .map(|&len| (len, 0)),
+    );
+
+    // A seeded RNG keeps the inputs, and so the cycle counts, comparable between runs
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut cases = vec![];
+    for key_len in KEY_SIZES {
+        for &(aad_len, plaintext_len) in &sizes {
+            let test_case = random_gcm_test_case(&mut rng, key_len, aad_len, plaintext_len);
+
+            cases.push(SweepCase {
+                key_len,
+                aad_len,
+                plaintext_len,
+                test_case: TestCase("AES-GCM-vec".to_string(), test_case.to_bytes()),
+            });
+            cases.push(SweepCase {
+                key_len,
+                aad_len,
+                plaintext_len,
+                test_case: TestCase(
+                    "AES-GCM-vec-native".to_string(),
+                    AesGcmVecNativeTestCase(test_case).to_bytes(),
+                ),
+            });
+        }
+    }
+    cases
+}
+
+pub(crate) fn run_sweep(options: &Options) {
+    let mut table = Table::new();
+    let mut header = row![
+        "Name",
+        "Key (bits)",
+        "AAD (bytes)",
+        "Plaintext (bytes)",
+        "Total Cycles",
+        "User Cycles",
+        "User Cycles/Byte",
+        if options.execute_only {
+            "Execution (ms)"
+        } else {
+            "Proving (ms)"
+        }
+    ];
+    if options.succinct {
+        header.add_cell(Cell::new("Compression (ms)"));
+        header.add_cell(Cell::new("Succinct Seal (bytes)"));
+    }
+    table.add_row(header);
+
+    let mut profiles = vec![];
+    for case in sweep_cases(options.seed) {
+        let (name, duration, total_cycles, user_cycles, profile, compression) =
+            if options.execute_only {
+                let data = execute(&case.test_case);
+                (
+                    data.name,
+                    data.duration,
+                    data.total_cycles,
+                    data.user_cycles,
+                    data.profile,
+                    None,
+                )
+            } else {
+                let data = prove(&case.test_case, options);
+                (
+                    data.name,
+                    data.duration,
+                    data.cycles,
+                    data.user_cycles,
+                    data.profile,
+                    data.compression.zip(data.succinct_seal),
+                )
+            };
+
+        let bytes = case.aad_len + case.plaintext_len;
+        let cycles_per_byte = if bytes == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.2}", user_cycles as f64 / bytes as f64)
+        };
+
+        profiles.push((
+            format!(
+                "{} ({}-bit key, {} B AAD, {} B plaintext)",
+                name,
+                case.key_len * 8,
+                case.aad_len,
+                case.plaintext_len
+            ),
+            profile,
+        ));
+        let mut row = row![
+            name,
+            case.key_len * 8,
+            case.aad_len,
+            case.plaintext_len,
+            total_cycles,
+            user_cycles,
+            cycles_per_byte,
+            duration.as_millis()
+        ];
+        if let Some((compression, succinct_seal)) = compression {
+            row.add_cell(Cell::new(&compression.as_millis().to_string()));
+            row.add_cell(Cell::new(&succinct_seal.to_string()));
+        }
+        table.add_row(row);
+    }
+
+    println!("### Sweep Data");
+    table.printstd();
+    print_cycle_breakdown(&profiles);
+}
@@ -1,3 +1,23 @@
+use risc0_build::GuestOptions;
+use std::collections::HashMap;
+
 fn main() {
-    risc0_build::embed_methods();
+    let mut features = vec![];
+    if std::env::var("CARGO_FEATURE_PROFILE").is_ok() {
+        features.push("profile".to_string());
+    }
+    if std::env::var("CARGO_FEATURE_T_TABLE").is_ok() {
+        features.push("t-table".to_string());
+    }
+    if std::env::var("CARGO_FEATURE_KARATSUBA").is_ok() {
+        features.push("karatsuba".to_string());
+    }
+
+    risc0_build::embed_methods_with_options(HashMap::from([(
+        "guest_code_for_zk_proof",
+        GuestOptions {
+            features,
+            ..Default::default()
+        },
+    )]));
 }
@@ -1,26 +1,82 @@
 use risc0_zkvm::guest::env;
-use shared::{AesGcmNativeTestCase, AesGcmTestCase, AesTestCase, TestCase};
+use risc0_zkvm::sha::{Impl, Sha256};
+use shared::{
+    AesCcmTestCase, AesGcmNativeTestCase, AesGcmTestCase, AesGcmVecNativeTestCase,
+    AesGcmVecTestCase, AesTestCase, CmacTestCase, GmacTestCase, GuestOutput, KeyUnwrapTestCase,
+    TestCase,
+};
 
 fn main() {
     let serialized_inputs: Vec<u8> = env::read();
     let test_case = TestCase::from_bytes(&serialized_inputs);
 
-    if test_case.0 == "AES" {
+    #[cfg(feature = "profile")]
+    shared::profile::set_cycle_counter(env::cycle_count);
+
+    let mut authenticated = None;
+    let mut unwrapped = None;
+    let is_valid = if test_case.0 == "AES" {
         let concrete_test_case = AesTestCase::from_bytes(&test_case.1.to_vec());
-        let is_valid = concrete_test_case.is_valid();
-        assert!(is_valid);
-        env::commit(&is_valid);
+        concrete_test_case.is_valid()
+    } else if test_case.0 == "AES-T-table" {
+        let concrete_test_case = AesTestCase::from_bytes(&test_case.1.to_vec());
+        concrete_test_case.is_valid_t_table()
     } else if test_case.0 == "AES-GCM" {
         let concrete_test_case = AesGcmTestCase::from_bytes(&test_case.1.to_vec());
-        let is_valid = concrete_test_case.is_valid();
-        assert!(is_valid);
-        env::commit(&is_valid);
+        concrete_test_case.is_valid()
     } else if test_case.0 == "AES-GCM-native" {
         let concrete_test_case = AesGcmNativeTestCase::from_bytes(&test_case.1.to_vec());
+        concrete_test_case.is_valid()
+    } else if test_case.0 == "AES-GCM-vec" {
+        let concrete_test_case = AesGcmVecTestCase::from_bytes(&test_case.1.to_vec());
+        concrete_test_case.is_valid_in_place()
+    } else if test_case.0 == "AES-GCM-vec-native" {
+        let concrete_test_case = AesGcmVecNativeTestCase::from_bytes(&test_case.1.to_vec());
+        concrete_test_case.is_valid()
+    } else if test_case.0 == "AES-CCM" {
+        let concrete_test_case = AesCcmTestCase::from_bytes(&test_case.1.to_vec());
+        concrete_test_case.is_valid()
+    } else if test_case.0 == "CMAC" {
+        let concrete_test_case = CmacTestCase::from_bytes(&test_case.1.to_vec());
+        concrete_test_case.is_valid()
+    } else if test_case.0 == "GMAC" {
+        let concrete_test_case = GmacTestCase::from_bytes(&test_case.1.to_vec());
         let is_valid = concrete_test_case.is_valid();
-        assert!(is_valid);
-        env::commit(&is_valid);
+        let key_digest = Impl::hash_bytes(&concrete_test_case.key);
+        let mut key_digest_bytes = [0; 32];
+        key_digest_bytes.copy_from_slice(key_digest.as_bytes());
+        authenticated = Some((
+            concrete_test_case.data,
+            concrete_test_case.iv,
+            concrete_test_case.tag,
+            key_digest_bytes,
+        ));
+        is_valid
+    } else if test_case.0 == "AES-KW-unwrap" {
+        let concrete_test_case = KeyUnwrapTestCase::from_bytes(&test_case.1.to_vec());
+        let is_valid = concrete_test_case.is_valid();
+        let kek_digest = Impl::hash_bytes(&concrete_test_case.kek);
+        let mut kek_digest_bytes = [0; 32];
+        kek_digest_bytes.copy_from_slice(kek_digest.as_bytes());
+        unwrapped = Some((concrete_test_case.wrapped_key, kek_digest_bytes));
+        is_valid
     } else {
         panic!("Not a valid test case: {}", test_case.0)
-    }
+    };
+    assert!(is_valid);
+
+    #[cfg(feature = "profile")]
+    let profile = shared::profile::report()
+        .into_iter()
+        .map(|(phase, cycles)| (phase.name().to_string(), cycles))
+        .collect();
+    #[cfg(not(feature = "profile"))]
+    let profile = vec![];
+
+    env::commit(&GuestOutput {
+        is_valid,
+        profile,
+        authenticated,
+        unwrapped,
+    });
 }
@@ -1,8 +1,15 @@
-use aes_gcm::aead::Aead;
-use aes_gcm::{Aes128Gcm, Key, KeyInit, Nonce};
+use aes_gcm::aead::consts::U12;
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::aes::Aes192;
+use aes_gcm::{Aes128Gcm, Aes256Gcm, AesGcm, Key, KeyInit, Nonce};
 pub use aes_gcm_mini::{Block, Stream};
 
-use aes_gcm_mini::{aes, aes_gcm};
+#[cfg(feature = "profile")]
+pub use aes_gcm_mini::profile;
+use aes_gcm_mini::{
+    aes, aes_gcm, aes_gcm_encrypt, aes_gcm_encrypt_in_place, aes_t_table, ccm_decrypt, ccm_encrypt,
+    cmac, gmac_verify, kw_unwrap, kwp_unwrap, AesKey, Error,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug, Deserialize, PartialEq)]
@@ -38,6 +45,9 @@ impl AesTestCase {
     pub fn is_valid(&self) -> bool {
         aes(self.block, self.key) == self.cipher
     }
+    pub fn is_valid_t_table(&self) -> bool {
+        aes_t_table(self.block, self.key) == self.cipher
+    }
     pub fn to_bytes(&self) -> Vec<u8> {
         bincode::serialize(&self).unwrap()
     }
@@ -50,6 +60,7 @@ pub struct AesGcmTestCase {
     pub aad: Stream,
     pub plaintext: Stream,
     expected_output: Stream,
+    expected_tag: Stream,
 }
 
 impl AesGcmTestCase {
@@ -62,20 +73,25 @@ impl AesGcmTestCase {
             0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2, 0xb9, 0x71, 0xb2,
             0xfe, 0x78,
         ];
+        let expected_tag = [
+            0xd2, 0x4e, 0x50, 0x3a, 0x1b, 0xb0, 0x37, 0x07, 0x1c, 0x71, 0xb3, 0x5d, 0x98, 0x7b,
+            0x86, 0x57,
+        ];
         Self {
             key,
             plaintext,
             iv,
             aad,
             expected_output,
+            expected_tag,
         }
     }
     pub fn from_bytes(bytes: &[u8]) -> Self {
         bincode::deserialize(bytes).unwrap()
     }
     pub fn is_valid(&self) -> bool {
-        let (ciphertext, _tag) = aes_gcm(self.key, self.plaintext, self.iv, self.aad);
-        ciphertext == self.expected_output
+        let (ciphertext, tag) = aes_gcm(self.key, self.plaintext, self.iv, self.aad);
+        ciphertext == self.expected_output && tag == self.expected_tag
     }
     pub fn to_bytes(&self) -> Vec<u8> {
         bincode::serialize(&self).unwrap()
@@ -101,23 +117,419 @@ impl AesGcmNativeTestCase {
     }
 
     pub fn is_valid(&self) -> bool {
-        let key = Key::<Aes128Gcm>::from_slice(&self.0.key);
-        let cipher = Aes128Gcm::new(key);
+        let test_case = &self.0;
+        match native_aes_gcm_encrypt(
+            &test_case.key,
+            &test_case.iv,
+            &test_case.aad,
+            &test_case.plaintext,
+        ) {
+            Some(output) => {
+                let (ciphertext, tag) = output.split_at(output.len() - 16);
+                ciphertext == test_case.expected_output && tag == test_case.expected_tag
+            }
+            None => false,
+        }
+    }
+}
 
-        let nonce = Nonce::from_slice(&self.0.iv);
-        let result = cipher.encrypt(nonce, self.0.plaintext.as_ref());
+/// AES-GCM over inputs of arbitrary length and with any of the AES key sizes.
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+pub struct AesGcmVecTestCase {
+    pub key: Vec<u8>,
+    pub iv: [u8; 12],
+    pub aad: Vec<u8>,
+    pub plaintext: Vec<u8>,
+    expected_ciphertext: Vec<u8>,
+    expected_tag: Stream,
+}
 
-        match result {
-            Ok(ciphertext) => {
-                let mut result = [0u8; 16];
-                result.copy_from_slice(&ciphertext[..16]); // Copy only the first 16 bytes (block)
-                result == self.0.expected_output
+impl AesGcmVecTestCase {
+    /// Creates a test case whose expected output is computed with RustCrypto's `aes-gcm`.
+    pub fn new(key: Vec<u8>, iv: [u8; 12], aad: Vec<u8>, plaintext: Vec<u8>) -> Self {
+        let mut expected_ciphertext = native_aes_gcm_encrypt(&key, &iv, &aad, &plaintext)
+            .expect("Native AES-GCM encryption failed");
+        let tag_start = expected_ciphertext.len() - 16;
+        let mut expected_tag: Stream = [0; 16];
+        expected_tag.copy_from_slice(&expected_ciphertext[tag_start..]);
+        expected_ciphertext.truncate(tag_start);
+        Self {
+            key,
+            iv,
+            aad,
+            plaintext,
+            expected_ciphertext,
+            expected_tag,
+        }
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        bincode::deserialize(bytes).unwrap()
+    }
+    pub fn is_valid(&self) -> bool {
+        let (ciphertext, tag) = match self.key.len() {
+            16 => aes_gcm_encrypt(
+                <[u8; 16]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.aad,
+                &self.plaintext,
+            ),
+            24 => aes_gcm_encrypt(
+                <[u8; 24]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.aad,
+                &self.plaintext,
+            ),
+            32 => aes_gcm_encrypt(
+                <[u8; 32]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.aad,
+                &self.plaintext,
+            ),
+            _ => return false,
+        };
+        ciphertext == self.expected_ciphertext && tag == self.expected_tag
+    }
+    /// Same as `is_valid`, but encrypts the plaintext in its own buffer instead of a copy of
+    /// it, which saves the guest paging in a second buffer for large payloads.
+    pub fn is_valid_in_place(self) -> bool {
+        let mut buffer = self.plaintext;
+        let tag = match self.key.len() {
+            16 => aes_gcm_encrypt_in_place(
+                <[u8; 16]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.aad,
+                &mut buffer,
+            ),
+            24 => aes_gcm_encrypt_in_place(
+                <[u8; 24]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.aad,
+                &mut buffer,
+            ),
+            32 => aes_gcm_encrypt_in_place(
+                <[u8; 32]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.aad,
+                &mut buffer,
+            ),
+            _ => return false,
+        };
+        buffer == self.expected_ciphertext && tag == self.expected_tag
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self).unwrap()
+    }
+}
+
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+pub struct AesGcmVecNativeTestCase(
+    // We reuse the same data
+    pub AesGcmVecTestCase,
+);
+
+impl AesGcmVecNativeTestCase {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        bincode::deserialize(bytes).unwrap()
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self).unwrap()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let test_case = &self.0;
+        match native_aes_gcm_encrypt(
+            &test_case.key,
+            &test_case.iv,
+            &test_case.aad,
+            &test_case.plaintext,
+        ) {
+            Some(output) => {
+                let (ciphertext, tag) = output.split_at(output.len() - 16);
+                ciphertext == test_case.expected_ciphertext && tag == test_case.expected_tag
             }
-            Err(_) => false,
+            None => false,
         }
     }
 }
 
+/// AES-CMAC with any of the AES key sizes.
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+pub struct CmacTestCase {
+    pub key: Vec<u8>,
+    pub message: Vec<u8>,
+    expected_mac: Stream,
+}
+
+impl CmacTestCase {
+    pub fn default_case() -> Self {
+        // RFC 4493, example 3
+        let key = vec![
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ];
+        let message = vec![
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+            0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac,
+            0x45, 0xaf, 0x8e, 0x51, 0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11,
+        ];
+        let expected_mac = [
+            0xdf, 0xa6, 0x67, 0x47, 0xde, 0x9a, 0xe6, 0x30, 0x30, 0xca, 0x32, 0x61, 0x14, 0x97,
+            0xc8, 0x27,
+        ];
+        Self {
+            key,
+            message,
+            expected_mac,
+        }
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        bincode::deserialize(bytes).unwrap()
+    }
+    pub fn is_valid(&self) -> bool {
+        let mac = match self.key.len() {
+            16 => cmac(
+                <[u8; 16]>::try_from(self.key.as_slice()).unwrap(),
+                &self.message,
+            ),
+            24 => cmac(
+                <[u8; 24]>::try_from(self.key.as_slice()).unwrap(),
+                &self.message,
+            ),
+            32 => cmac(
+                <[u8; 32]>::try_from(self.key.as_slice()).unwrap(),
+                &self.message,
+            ),
+            _ => return false,
+        };
+        mac == self.expected_mac
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self).unwrap()
+    }
+}
+
+/// AES-GMAC authentication of data, which the guest commits to the journal together with its tag.
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+pub struct GmacTestCase {
+    pub key: Vec<u8>,
+    pub iv: [u8; 12],
+    pub data: Vec<u8>,
+    pub tag: Stream,
+}
+
+impl GmacTestCase {
+    pub fn default_case() -> Self {
+        let key = vec![0x42; 16];
+        let data = b"The data is authenticated, but not encrypted".to_vec();
+        Self::new(key, [0x24; 12], data)
+    }
+    /// Creates a test case whose tag is computed with RustCrypto's `aes-gcm`.
+    pub fn new(key: Vec<u8>, iv: [u8; 12], data: Vec<u8>) -> Self {
+        let output = native_aes_gcm_encrypt(&key, &iv, &data, &[]).expect("Native AES-GMAC failed");
+        let mut tag: Stream = [0; 16];
+        tag.copy_from_slice(&output);
+        Self { key, iv, data, tag }
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        bincode::deserialize(bytes).unwrap()
+    }
+    pub fn is_valid(&self) -> bool {
+        let result = match self.key.len() {
+            16 => gmac_verify(
+                <[u8; 16]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.data,
+                &self.tag,
+            ),
+            24 => gmac_verify(
+                <[u8; 24]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.data,
+                &self.tag,
+            ),
+            32 => gmac_verify(
+                <[u8; 32]>::try_from(self.key.as_slice()).unwrap(),
+                self.iv,
+                &self.data,
+                &self.tag,
+            ),
+            _ => return false,
+        };
+        result.is_ok()
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self).unwrap()
+    }
+}
+
+/// AES-CCM with any of the AES key sizes, nonce and tag lengths.
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+pub struct AesCcmTestCase {
+    pub key: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub aad: Vec<u8>,
+    pub plaintext: Vec<u8>,
+    expected_ciphertext: Vec<u8>,
+    expected_tag: Vec<u8>,
+}
+
+impl AesCcmTestCase {
+    pub fn default_case() -> Self {
+        // RFC 3610, packet vector #1
+        let key = (0xc0..=0xcf).collect();
+        let nonce = vec![
+            0x00, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5,
+        ];
+        let aad = (0x00..=0x07).collect();
+        let plaintext = (0x08..=0x1e).collect();
+        let expected_ciphertext = vec![
+            0x58, 0x8c, 0x97, 0x9a, 0x61, 0xc6, 0x63, 0xd2, 0xf0, 0x66, 0xd0, 0xc2, 0xc0, 0xf9,
+            0x89, 0x80, 0x6d, 0x5f, 0x6b, 0x61, 0xda, 0xc3, 0x84,
+        ];
+        let expected_tag = vec![0x17, 0xe8, 0xd1, 0x2c, 0xfd, 0xf9, 0x26, 0xe0];
+        Self {
+            key,
+            nonce,
+            aad,
+            plaintext,
+            expected_ciphertext,
+            expected_tag,
+        }
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        bincode::deserialize(bytes).unwrap()
+    }
+    pub fn is_valid(&self) -> bool {
+        let result = match self.key.len() {
+            16 => self.check(<[u8; 16]>::try_from(self.key.as_slice()).unwrap()),
+            24 => self.check(<[u8; 24]>::try_from(self.key.as_slice()).unwrap()),
+            32 => self.check(<[u8; 32]>::try_from(self.key.as_slice()).unwrap()),
+            _ => return false,
+        };
+        result.unwrap_or(false)
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self).unwrap()
+    }
+
+    // Checks both directions, the encryption and the authenticated decryption
+    fn check<K: AesKey>(&self, key: K) -> Result<bool, Error> {
+        let (ciphertext, tag) = ccm_encrypt(
+            key,
+            &self.nonce,
+            &self.aad,
+            &self.plaintext,
+            self.expected_tag.len(),
+        )?;
+        let plaintext = ccm_decrypt(
+            key,
+            &self.nonce,
+            &self.aad,
+            &self.expected_ciphertext,
+            &self.expected_tag,
+        )?;
+        Ok(ciphertext == self.expected_ciphertext
+            && tag == self.expected_tag
+            && plaintext == self.plaintext)
+    }
+}
+
+/// AES Key Unwrap, with or without padding, of a data key wrapped with a key encryption key.
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+pub struct KeyUnwrapTestCase {
+    pub kek: Vec<u8>,
+    pub wrapped_key: Vec<u8>,
+    /// Whether the key was wrapped with padding (RFC 5649) rather than without (RFC 3394).
+    pub padded: bool,
+    expected_key: Vec<u8>,
+}
+
+impl KeyUnwrapTestCase {
+    pub fn default_case() -> Self {
+        // RFC 3394, 4.1 wrap 128 bits of key data with a 128-bit KEK
+        let kek = (0x00..=0x0f).collect();
+        let wrapped_key = vec![
+            0x1f, 0xa6, 0x8b, 0x0a, 0x81, 0x12, 0xb4, 0x47, 0xae, 0xf3, 0x4b, 0xd8, 0xfb, 0x5a,
+            0x7b, 0x82, 0x9d, 0x3e, 0x86, 0x23, 0x71, 0xd2, 0xcf, 0xe5,
+        ];
+        let expected_key = vec![
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        Self {
+            kek,
+            wrapped_key,
+            padded: false,
+            expected_key,
+        }
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        bincode::deserialize(bytes).unwrap()
+    }
+    pub fn is_valid(&self) -> bool {
+        let result = match self.kek.len() {
+            16 => self.unwrap(<[u8; 16]>::try_from(self.kek.as_slice()).unwrap()),
+            24 => self.unwrap(<[u8; 24]>::try_from(self.kek.as_slice()).unwrap()),
+            32 => self.unwrap(<[u8; 32]>::try_from(self.kek.as_slice()).unwrap()),
+            _ => return false,
+        };
+        result.is_ok_and(|key| key == self.expected_key)
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self).unwrap()
+    }
+
+    fn unwrap<K: AesKey>(&self, kek: K) -> Result<Vec<u8>, Error> {
+        if self.padded {
+            kwp_unwrap(kek, &self.wrapped_key)
+        } else {
+            kw_unwrap(kek, &self.wrapped_key)
+        }
+    }
+}
+
+/// Encrypts with RustCrypto's `aes-gcm`, returning the ciphertext followed by the tag.
+fn native_aes_gcm_encrypt(
+    key: &[u8],
+    iv: &[u8; 12],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Option<Vec<u8>> {
+    let nonce = Nonce::from_slice(iv);
+    let payload = Payload {
+        msg: plaintext,
+        aad,
+    };
+    let result = match key.len() {
+        16 => Aes128Gcm::new(Key::<Aes128Gcm>::from_slice(key)).encrypt(nonce, payload),
+        24 => AesGcm::<Aes192, U12>::new_from_slice(key)
+            .unwrap()
+            .encrypt(nonce, payload),
+        32 => Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)).encrypt(nonce, payload),
+        _ => return None,
+    };
+    result.ok()
+}
+
+/// The data, IV, tag and key digest a GMAC test case commits to the journal.
+pub type GmacStatement = (Vec<u8>, [u8; 12], Stream, [u8; 32]);
+
+/// The result committed to the journal by the guest.
+#[derive(Serialize, Debug, Deserialize, PartialEq)]
+pub struct GuestOutput {
+    pub is_valid: bool,
+    /// Cycles spent in each AES-GCM primitive, empty unless the guest is built with the
+    /// `profile` feature.
+    pub profile: Vec<(String, u64)>,
+    /// The data, IV and tag of a GMAC test case and the SHA-256 digest of its key, so the
+    /// receipt attests that the tag is valid for exactly this data under this IV and the
+    /// committed key, without revealing the key.
+    pub authenticated: Option<GmacStatement>,
+    /// The wrapped key of a key unwrap test case and the SHA-256 digest of the KEK, so the
+    /// receipt attests that the key was unwrapped with the committed KEK.
+    pub unwrapped: Option<(Vec<u8>, [u8; 32])>,
+}
+
 #[derive(Serialize, Debug, Deserialize, PartialEq)]
 pub struct TestCase(pub String, pub Vec<u8>);
 
@@ -137,6 +549,7 @@ mod test {
     fn test_aes() {
         let test_case = AesTestCase::default_case();
         assert!(test_case.is_valid());
+        assert!(test_case.is_valid_t_table());
 
         let serialized = test_case.to_bytes();
         let deserialized = AesTestCase::from_bytes(&serialized);
@@ -162,4 +575,136 @@ mod test {
         let deserialized = AesGcmNativeTestCase::from_bytes(&serialized);
         assert_eq!(test_case, deserialized);
     }
+
+    #[test]
+    fn test_aes_gcm_aad_is_authenticated() {
+        let mut test_case = AesGcmTestCase::default_case();
+        test_case.aad[0] ^= 1;
+        assert!(!test_case.is_valid());
+        assert!(!AesGcmNativeTestCase(test_case).is_valid());
+    }
+
+    #[test]
+    fn test_aes_gcm_vec() {
+        for key_len in [16, 24, 32] {
+            for len in [0, 1, 16, 17, 64] {
+                let key = (0..key_len as u8).collect();
+                let aad = (0..len as u8).rev().collect();
+                let plaintext = (0..len as u8).collect();
+                let test_case = AesGcmVecTestCase::new(key, [0x42; 12], aad, plaintext);
+                assert!(test_case.is_valid());
+
+                let serialized = test_case.to_bytes();
+                let deserialized = AesGcmVecTestCase::from_bytes(&serialized);
+                assert_eq!(test_case, deserialized);
+
+                let native_test_case = AesGcmVecNativeTestCase(deserialized);
+                assert!(native_test_case.is_valid());
+                assert!(AesGcmVecTestCase::from_bytes(&serialized).is_valid_in_place());
+            }
+        }
+    }
+
+    // RustCrypto's `aes-gcm` supports tags of 12 to 16 bytes
+    fn native_aes_gcm_truncated<T: aes_gcm::TagSize>(
+        key: &[u8],
+        iv: &[u8; 12],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Vec<u8> {
+        AesGcm::<aes_gcm::aes::Aes128, U12, T>::new_from_slice(key)
+            .unwrap()
+            .encrypt(
+                Nonce::from_slice(iv),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_aes_gcm_truncated_tags() {
+        use aes_gcm::aead::consts::{U13, U14, U15, U16};
+        use aes_gcm_mini::aes_gcm_encrypt_truncated;
+
+        let key = [0x42u8; 16];
+        let iv = [0x24; 12];
+        let aad: Vec<u8> = (0..20).collect();
+        let plaintext: Vec<u8> = (0..70).rev().collect();
+        let native_outputs = [
+            native_aes_gcm_truncated::<U12>(&key, &iv, &aad, &plaintext),
+            native_aes_gcm_truncated::<U13>(&key, &iv, &aad, &plaintext),
+            native_aes_gcm_truncated::<U14>(&key, &iv, &aad, &plaintext),
+            native_aes_gcm_truncated::<U15>(&key, &iv, &aad, &plaintext),
+            native_aes_gcm_truncated::<U16>(&key, &iv, &aad, &plaintext),
+        ];
+        for (tag_len, native_output) in (12..=16).zip(native_outputs) {
+            let (ciphertext, tag) =
+                aes_gcm_encrypt_truncated(key, iv, &aad, &plaintext, tag_len).unwrap();
+            assert_eq!([ciphertext, tag].concat(), native_output);
+        }
+    }
+
+    #[test]
+    fn test_aes_ccm() {
+        let test_case = AesCcmTestCase::default_case();
+        assert!(test_case.is_valid());
+
+        let serialized = test_case.to_bytes();
+        let deserialized = AesCcmTestCase::from_bytes(&serialized);
+        assert_eq!(test_case, deserialized);
+
+        let mut forged_test_case = AesCcmTestCase::default_case();
+        forged_test_case.aad[0] ^= 1;
+        assert!(!forged_test_case.is_valid());
+    }
+
+    #[test]
+    fn test_cmac() {
+        let test_case = CmacTestCase::default_case();
+        assert!(test_case.is_valid());
+
+        let serialized = test_case.to_bytes();
+        let deserialized = CmacTestCase::from_bytes(&serialized);
+        assert_eq!(test_case, deserialized);
+    }
+
+    #[test]
+    fn test_key_unwrap() {
+        let test_case = KeyUnwrapTestCase::default_case();
+        assert!(test_case.is_valid());
+
+        let serialized = test_case.to_bytes();
+        let deserialized = KeyUnwrapTestCase::from_bytes(&serialized);
+        assert_eq!(test_case, deserialized);
+
+        let mut padded_test_case = KeyUnwrapTestCase::default_case();
+        padded_test_case.padded = true;
+        assert!(!padded_test_case.is_valid());
+
+        let mut forged_test_case = KeyUnwrapTestCase::default_case();
+        forged_test_case.wrapped_key[0] ^= 1;
+        assert!(!forged_test_case.is_valid());
+    }
+
+    #[test]
+    fn test_gmac() {
+        let test_case = GmacTestCase::default_case();
+        assert!(test_case.is_valid());
+
+        let serialized = test_case.to_bytes();
+        let deserialized = GmacTestCase::from_bytes(&serialized);
+        assert_eq!(test_case, deserialized);
+
+        for key_len in [24, 32] {
+            let key = (0..key_len as u8).collect();
+            assert!(GmacTestCase::new(key, [0x42; 12], vec![0x11; 33]).is_valid());
+        }
+
+        let mut forged_test_case = GmacTestCase::default_case();
+        forged_test_case.data[0] ^= 1;
+        assert!(!forged_test_case.is_valid());
+    }
 }